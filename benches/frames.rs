@@ -0,0 +1,49 @@
+// Emulation throughput benchmark — frames per second of the core, without SDL.
+//
+// Compiles the demo games with the Shrimp compiler and runs each headless for
+// a fixed number of frames from the DMG post-boot state, once with each PPU
+// renderer.
+//
+//   cargo bench --no-default-features --bench frames
+
+use emulator::boot::{Boot, Model};
+use emulator::gpu::Renderer;
+use emulator::GameBoy;
+use std::time::Instant;
+
+const FRAMES: u32 = 1200;
+
+const GAMES: [(&str, &str); 2] = [
+    ("pong", include_str!("../games/pong.s")),
+    ("platformer", include_str!("../games/platformer.s")),
+];
+
+fn run_frames(rom: Vec<u8>, renderer: Renderer, frames: u32) {
+    let mut gb =
+        GameBoy::new(rom, Some(Boot::Skip(Model::Dmg))).expect("demo ROMs use a supported mapper");
+    gb.set_renderer(renderer);
+    for _ in 0..frames {
+        gb.run_frame();
+        gb.take_samples();
+    }
+}
+
+fn main() {
+    for (name, source) in GAMES {
+        let rom = compiler::compile(source).expect("demo game compiles");
+        for (renderer, label) in [(Renderer::Scanline, "scanline"), (Renderer::Fifo, "fifo")] {
+            let start = Instant::now();
+            run_frames(rom.clone(), renderer, FRAMES);
+            let elapsed = start.elapsed().as_secs_f64();
+            println!(
+                "{:<12} {:<9} {} frames in {:.2}s  {:>8.1} fps  ({:.1}x real time)",
+                name,
+                label,
+                FRAMES,
+                elapsed,
+                FRAMES as f64 / elapsed,
+                FRAMES as f64 / elapsed / 59.7275
+            );
+        }
+    }
+}
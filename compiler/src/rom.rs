@@ -6,7 +6,7 @@
 //!   $0100-$0103  Cartridge entry point (NOP; JP $0150)
 //!   $0104-$0133  Nintendo logo
 //!   $0134-$014F  ROM header
-//!   $0150        LCD init, tile VRAM copy, OAM clear, user init call, EI, HALT loop
+//!   $0150        LCD init, tile VRAM copy, OAM clear, user init call, LCD on, EI, HALT loop
 //!   $0200+       Compiled user code (init fn + vblank fn + builtins)
 //!   Near end     Tile pixel data
 
@@ -133,7 +133,7 @@ impl RomWriter {
 
         // ── Setup code ($0150) ────────────────────────────────────────────
         // DI; LD SP,$FFFE; turn off LCD; copy tiles; clear OAM; load palettes;
-        // enable LCD; call user init; EI; HALT loop.
+        // call user init; enable LCD; EI; HALT loop.
         const VRAM_TILE_BASE: u16 = 0x8000;
         let n_tiles = tile_data.len() / 16;
         let tile_end_addr = VRAM_TILE_BASE + (tile_data.len() as u16);
@@ -145,7 +145,7 @@ impl RomWriter {
         setup.extend_from_slice(&[0x31, 0xFE, 0xFF]);
         // Wait for VBlank before touching VRAM: poll LY >= 144
         //   vblank_wait: LD A,(FF44); CP 144; JR C, vblank_wait
-        setup.extend_from_slice(&[0xF0, 0x44, 0xFE, 0x90, 0x38, 0xFB]); // LDH A,(44); CP 144; JR C,-5
+        setup.extend_from_slice(&[0xF0, 0x44, 0xFE, 0x90, 0x38, 0xFA]); // LDH A,(44); CP 144; JR C,-6
                                                                         // LCD off: LD A,0; LD (FF40), A
         setup.extend_from_slice(&[0x3E, 0x00, 0xE0, 0x40]);
 
@@ -205,18 +205,23 @@ impl RomWriter {
         setup.extend_from_slice(&[0xE0, 0x48]); // LDH (48),A  OBP0
         setup.extend_from_slice(&[0xE0, 0x49]); // LDH (49),A  OBP1
 
-        // LCD on: LD A,$93; LDH (40),A   (LCD on, BG on, Sprites on, tile data at $8000)
-        // $93 = bit7 (LCD on) | bit4 (BG tile $8000) | bit1 (OBJ/sprites on) | bit0 (BG on)
-        setup.extend_from_slice(&[0x3E, 0x93, 0xE0, 0x40]);
-
         // Enable VBlank interrupt: LD A,$01; LD (FFFF),A
         setup.extend_from_slice(&[0x3E, 0x01, 0xEA, 0xFF, 0xFF]);
 
-        // Call user init (at GAME_CODE_START)
+        // Call user init (at GAME_CODE_START) while the LCD is still off, so its
+        // set_bg_tile/set_sprite writes are not locked out by the PPU
         let init_addr = GAME_CODE_START as u16;
         setup.extend_from_slice(&[0xCD]);
         setup.extend_from_slice(&init_addr.to_le_bytes());
 
+        // LCD on: LD A,$93; LDH (40),A   (LCD on, BG on, Sprites on, tile data at $8000)
+        // $93 = bit7 (LCD on) | bit4 (BG tile $8000) | bit1 (OBJ/sprites on) | bit0 (BG on)
+        setup.extend_from_slice(&[0x3E, 0x93, 0xE0, 0x40]);
+
+        // Drop the VBlank flag left over from before the LCD was turned off, so
+        // the handler first runs in a real VBlank: XOR A; LDH (0F),A
+        setup.extend_from_slice(&[0xAF, 0xE0, 0x0F]);
+
         // EI
         setup.push(0xFB);
         // Halt loop: HALT; JR -2
@@ -8,7 +8,7 @@
 //
 // Control: NR50/NR51/NR52               (0xFF24–0xFF26)
 
-#![allow(dead_code)]
+use crate::savestate::{StateError, StateReader, StateWriter};
 
 const CPU_FREQ: u32 = 4_194_304;
 pub const SAMPLE_RATE: u32 = 44_100;
@@ -140,6 +140,68 @@ impl SquareChannel {
         }
     }
 
+    fn save_state(&self, w: &mut StateWriter) {
+        for value in [
+            self.duty,
+            self.volume,
+            self.env_initial,
+            self.env_period,
+            self.env_timer,
+            self.sweep_period,
+            self.sweep_shift,
+            self.sweep_timer,
+            self.phase_step,
+        ] {
+            w.u8(value);
+        }
+        for value in [
+            self.env_add,
+            self.length_enable,
+            self.sweep_negate,
+            self.sweep_enabled,
+            self.enabled,
+            self.dac_enabled,
+        ] {
+            w.bool(value);
+        }
+        w.u16(self.freq);
+        w.i32(self.timer);
+        w.u16(self.length_counter);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        for value in [
+            &mut self.duty,
+            &mut self.volume,
+            &mut self.env_initial,
+            &mut self.env_period,
+            &mut self.env_timer,
+            &mut self.sweep_period,
+            &mut self.sweep_shift,
+            &mut self.sweep_timer,
+            &mut self.phase_step,
+        ] {
+            *value = r.u8()?;
+        }
+        for value in [
+            &mut self.env_add,
+            &mut self.length_enable,
+            &mut self.sweep_negate,
+            &mut self.sweep_enabled,
+            &mut self.enabled,
+            &mut self.dac_enabled,
+        ] {
+            *value = r.bool()?;
+        }
+        self.freq = r.u16()?;
+        self.timer = r.i32()?;
+        self.length_counter = r.u16()?;
+        if self.duty > 3 || self.phase_step > 7 {
+            return Err(StateError::Invalid("square channel"));
+        }
+        Ok(())
+    }
+
     /// Advance by `cycles` T-cycles; return raw amplitude 0-15.
     fn step(&mut self, cycles: u32) -> u8 {
         if !self.enabled {
@@ -211,6 +273,30 @@ impl WaveChannel {
         }
     }
 
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bool(self.dac_enabled);
+        w.u8(self.output_level);
+        w.u16(self.freq);
+        w.bool(self.length_enable);
+        w.bool(self.enabled);
+        w.i32(self.timer);
+        w.u8(self.position);
+        w.u16(self.length_counter);
+        w.bytes(&self.wave_ram);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.dac_enabled = r.bool()?;
+        self.output_level = r.u8()? & 0x03;
+        self.freq = r.u16()?;
+        self.length_enable = r.bool()?;
+        self.enabled = r.bool()?;
+        self.timer = r.i32()?;
+        self.position = r.u8()? & 31;
+        self.length_counter = r.u16()?;
+        r.bytes_into(&mut self.wave_ram)
+    }
+
     fn step(&mut self, cycles: u32) -> u8 {
         if !self.enabled || !self.dac_enabled {
             return 0;
@@ -299,6 +385,60 @@ impl NoiseChannel {
         }
     }
 
+    fn save_state(&self, w: &mut StateWriter) {
+        for value in [
+            self.volume,
+            self.env_initial,
+            self.env_period,
+            self.env_timer,
+            self.clock_shift,
+            self.divisor_code,
+        ] {
+            w.u8(value);
+        }
+        for value in [
+            self.env_add,
+            self.wide_mode,
+            self.length_enable,
+            self.enabled,
+            self.dac_enabled,
+        ] {
+            w.bool(value);
+        }
+        w.i32(self.timer);
+        w.u16(self.lfsr);
+        w.u16(self.length_counter);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        for value in [
+            &mut self.volume,
+            &mut self.env_initial,
+            &mut self.env_period,
+            &mut self.env_timer,
+            &mut self.clock_shift,
+            &mut self.divisor_code,
+        ] {
+            *value = r.u8()?;
+        }
+        for value in [
+            &mut self.env_add,
+            &mut self.wide_mode,
+            &mut self.length_enable,
+            &mut self.enabled,
+            &mut self.dac_enabled,
+        ] {
+            *value = r.bool()?;
+        }
+        self.timer = r.i32()?;
+        self.lfsr = r.u16()?;
+        self.length_counter = r.u16()?;
+        if self.divisor_code > 7 {
+            return Err(StateError::Invalid("noise channel"));
+        }
+        Ok(())
+    }
+
     fn step(&mut self, cycles: u32) -> u8 {
         if !self.enabled {
             return 0;
@@ -349,6 +489,12 @@ pub struct Apu {
     dc_prev_out_r: i32,
 }
 
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Apu {
     pub fn new() -> Self {
         Apu {
@@ -551,6 +697,41 @@ impl Apu {
         }
     }
 
+    pub fn save_state(&self, w: &mut StateWriter) {
+        self.ch1.save_state(w);
+        self.ch2.save_state(w);
+        self.ch3.save_state(w);
+        self.ch4.save_state(w);
+        w.u8(self.nr50);
+        w.u8(self.nr51);
+        w.bool(self.powered);
+        w.u32(self.frame_seq_timer);
+        w.u8(self.frame_seq_step);
+        w.u32(self.sample_accum);
+        w.i32(self.dc_prev_in_l);
+        w.i32(self.dc_prev_out_l);
+        w.i32(self.dc_prev_in_r);
+        w.i32(self.dc_prev_out_r);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ch1.load_state(r)?;
+        self.ch2.load_state(r)?;
+        self.ch3.load_state(r)?;
+        self.ch4.load_state(r)?;
+        self.nr50 = r.u8()?;
+        self.nr51 = r.u8()?;
+        self.powered = r.bool()?;
+        self.frame_seq_timer = r.u32()?;
+        self.frame_seq_step = r.u8()? & 7;
+        self.sample_accum = r.u32()?;
+        self.dc_prev_in_l = r.i32()?;
+        self.dc_prev_out_l = r.i32()?;
+        self.dc_prev_in_r = r.i32()?;
+        self.dc_prev_out_r = r.i32()?;
+        Ok(())
+    }
+
     /// Advance by `cycles` T-cycles. Returns `Some((left, right))` when a sample is ready.
     pub fn tick(&mut self, cycles: u32) -> Option<(i16, i16)> {
         if !self.powered {
@@ -0,0 +1,69 @@
+// disasm — prints a linear-sweep disassembly of a ROM, bank by bank.
+//
+// Each bank is listed at the addresses the CPU sees it at: bank 0 at
+// 0x0000–0x3FFF, every other bank at 0x4000–0x7FFF. Does not need SDL2:
+//   cargo build --release --no-default-features --bin disasm
+
+use emulator::disasm;
+use std::process;
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} <rom_path> [options]", program);
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --bank <n>            list only ROM bank <n> (default: every bank)");
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut rom_path = None;
+    let mut bank = None;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--bank" => match iter.next().and_then(|value| value.parse::<usize>().ok()) {
+                Some(n) => bank = Some(n),
+                None => {
+                    print_usage(&args[0]);
+                    process::exit(1);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("unknown option '{}'", arg);
+                print_usage(&args[0]);
+                process::exit(1);
+            }
+            _ => rom_path = Some(arg.clone()),
+        }
+    }
+    let Some(rom_path) = rom_path else {
+        print_usage(&args[0]);
+        process::exit(1);
+    };
+    let rom = match std::fs::read(&rom_path) {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("Failed to read ROM '{}': {}", rom_path, e);
+            process::exit(1);
+        }
+    };
+
+    let banks = match bank {
+        Some(n) => n..n + 1,
+        None => 0..rom.len().div_ceil(disasm::ROM_BANK_SIZE),
+    };
+    for n in banks {
+        match disasm::disassemble_bank(&rom, n) {
+            Ok(listing) => {
+                println!("; bank {}", n);
+                for instruction in listing {
+                    println!("{}", instruction);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,414 @@
+// Headless runner — runs a ROM without a window or audio device, for CI and
+// batch testing.
+//
+// The ROM runs for a fixed number of frames, or until a stop condition is met.
+// Scripted joypad input can be applied along the way. At the end the runner
+// prints the hash of the final frame to stdout and can dump that frame to PNG
+// and the audio to WAV. The exit code reports the outcome, so the runner can
+// act as a test oracle.
+//
+// Build without SDL2:
+//   cargo build --release --no-default-features --bin headless
+
+mod png;
+mod wav;
+
+use emulator::apu::SAMPLE_RATE;
+use emulator::boot::{Boot, Model};
+use emulator::gdb::{GdbStatus, GdbStub};
+use emulator::gpu::Renderer;
+use emulator::joypad::Button;
+use emulator::serial::CaptureSink;
+use emulator::{GameBoy, GameBoyError};
+use std::process;
+
+// Exit codes
+const EXIT_PASS: i32 = 0;
+// --expect-hash did not match, or --fail-serial text was printed
+const EXIT_FAIL: i32 = 1;
+// An --until condition was given but not met within --frames
+const EXIT_TIMEOUT: i32 = 2;
+// Bad arguments, unreadable files or unsupported ROM
+const EXIT_ERROR: i32 = 3;
+
+const DEFAULT_FRAMES: u32 = 600;
+
+const SCREEN_WIDTH: u32 = 160;
+const SCREEN_HEIGHT: u32 = 144;
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} <rom_path> [options]", program);
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!(
+        "  --frames <n>          frames to run, or the limit for --until-* (default {})",
+        DEFAULT_FRAMES
+    );
+    eprintln!("  --input <file>        scripted joypad input (see below)");
+    eprintln!("  --until-serial <text> stop and pass once the serial output contains <text>");
+    eprintln!("  --fail-serial <text>  stop and fail once the serial output contains <text>");
+    eprintln!("  --until-hash <hash>   stop and pass once a frame hashes to <hash>");
+    eprintln!("  --expect-hash <hash>  fail unless the final frame hashes to <hash>");
+    eprintln!("  --png <path>          write the final frame as a PNG");
+    eprintln!("  --wav <path>          write the audio output as a 16-bit stereo WAV");
+    eprintln!("  --bios <path>         run this boot ROM first instead of starting at 0x0100");
+    eprintln!("  --model <model>       post-boot state without --bios: dmg, mgb or cgb");
+    eprintln!("                        (default: cgb for cartridges with CGB support, else dmg)");
+    eprintln!("  --ppu <renderer>      scanline (default, fast) or fifo (dot-accurate)");
+    eprintln!("  --strict              report every VRAM/OAM access the PPU blocked, with its PC");
+    eprintln!("  --gdb <port>          wait for gdb on 127.0.0.1:<port> and start paused;");
+    eprintln!("                        frames only count while gdb lets the machine run");
+    eprintln!();
+    eprintln!("Input script: one '<frame> <buttons> [hold]' entry per line, e.g. '120 start 5'");
+    eprintln!("presses Start on frame 120 for 5 frames (default 1). Buttons are a, b, select,");
+    eprintln!("start, up, down, left and right, combined with '+'. '#' starts a comment.");
+    eprintln!();
+    eprintln!("Exit codes: 0 pass, 1 fail, 2 timed out, 3 error");
+}
+
+struct Options {
+    rom_path: String,
+    frames: u32,
+    input: Vec<InputEvent>,
+    until_serial: Option<String>,
+    fail_serial: Option<String>,
+    until_hash: Option<u64>,
+    expect_hash: Option<u64>,
+    png_path: Option<String>,
+    wav_path: Option<String>,
+    bios_path: Option<String>,
+    model: Option<Model>,
+    renderer: Renderer,
+    strict: bool,
+    gdb_port: Option<u16>,
+}
+
+/// Buttons held from `frame` for `hold` frames, as a `Button::mask` bitmask.
+struct InputEvent {
+    frame: u32,
+    hold: u32,
+    held: u8,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}", message);
+            }
+            print_usage(&args[0]);
+            process::exit(EXIT_ERROR);
+        }
+    };
+    process::exit(run(&options));
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        rom_path: String::new(),
+        frames: DEFAULT_FRAMES,
+        input: Vec::new(),
+        until_serial: None,
+        fail_serial: None,
+        until_hash: None,
+        expect_hash: None,
+        png_path: None,
+        wav_path: None,
+        bios_path: None,
+        model: None,
+        renderer: Renderer::Scanline,
+        strict: false,
+        gdb_port: None,
+    };
+    let mut rom_path = None;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            rom_path = Some(arg.clone());
+            continue;
+        }
+        if arg == "--strict" {
+            options.strict = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?
+            .clone();
+        match arg.as_str() {
+            "--frames" => {
+                options.frames = value
+                    .parse()
+                    .map_err(|_| format!("invalid frame count '{}'", value))?
+            }
+            "--input" => {
+                let script = std::fs::read_to_string(&value)
+                    .map_err(|e| format!("Failed to read input script '{}': {}", value, e))?;
+                options.input = parse_input_script(&script)?;
+            }
+            "--until-serial" => options.until_serial = Some(value),
+            "--fail-serial" => options.fail_serial = Some(value),
+            "--until-hash" => options.until_hash = Some(parse_hash(&value)?),
+            "--expect-hash" => options.expect_hash = Some(parse_hash(&value)?),
+            "--png" => options.png_path = Some(value),
+            "--wav" => options.wav_path = Some(value),
+            "--bios" => options.bios_path = Some(value),
+            "--model" => {
+                options.model = Some(
+                    Model::from_name(&value).ok_or_else(|| format!("unknown model '{}'", value))?,
+                )
+            }
+            "--ppu" => {
+                options.renderer = Renderer::from_name(&value)
+                    .ok_or_else(|| format!("unknown renderer '{}'", value))?
+            }
+            "--gdb" => {
+                options.gdb_port = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid gdb port '{}'", value))?,
+                )
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    options.rom_path = rom_path.ok_or_else(String::new)?;
+    Ok(options)
+}
+
+fn parse_hash(value: &str) -> Result<u64, String> {
+    let digits = value.trim_start_matches("0x");
+    u64::from_str_radix(digits, 16).map_err(|_| format!("invalid frame hash '{}'", value))
+}
+
+fn parse_input_script(script: &str) -> Result<Vec<InputEvent>, String> {
+    let mut events = Vec::new();
+    for (number, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |what: &str| format!("input script line {}: {}", number + 1, what);
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 2 || fields.len() > 3 {
+            return Err(error("expected '<frame> <buttons> [hold]'"));
+        }
+        let frame = fields[0]
+            .parse()
+            .map_err(|_| error("invalid frame number"))?;
+        let hold = match fields.get(2) {
+            Some(hold) => hold.parse().map_err(|_| error("invalid hold length"))?,
+            None => 1,
+        };
+        let mut held = 0u8;
+        for name in fields[1].split('+') {
+            let name = name.to_ascii_lowercase();
+            match Button::from_name(&name) {
+                Some(button) => held |= button.mask(),
+                None => return Err(error(&format!("unknown button '{}'", name))),
+            }
+        }
+        events.push(InputEvent { frame, hold, held });
+    }
+    Ok(events)
+}
+
+/// Buttons held during `frame`, as a `Button::mask` bitmask.
+fn held_at(events: &[InputEvent], frame: u32) -> u8 {
+    events
+        .iter()
+        .filter(|event| frame >= event.frame && frame - event.frame < event.hold)
+        .fold(0, |held, event| held | event.held)
+}
+
+fn run(options: &Options) -> i32 {
+    let rom = match std::fs::read(&options.rom_path) {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("Failed to read ROM '{}': {}", options.rom_path, e);
+            return EXIT_ERROR;
+        }
+    };
+    let boot = match &options.bios_path {
+        Some(path) => match std::fs::read(path) {
+            Ok(bios) => Some(Boot::Bios(bios)),
+            Err(e) => {
+                eprintln!("Failed to read boot ROM '{}': {}", path, e);
+                return EXIT_ERROR;
+            }
+        },
+        None => options.model.map(Boot::Skip),
+    };
+    let mut gb = match GameBoy::new(rom, boot) {
+        Ok(gb) => gb,
+        Err(GameBoyError::Boot(e)) => {
+            eprintln!("Failed to boot: {}", e);
+            return EXIT_ERROR;
+        }
+        Err(e) => {
+            eprintln!("Failed to load ROM '{}': {}", options.rom_path, e);
+            return EXIT_ERROR;
+        }
+    };
+    let sink = CaptureSink::new(false);
+    let serial = sink.output();
+    gb.set_serial_device(Box::new(sink));
+    gb.set_renderer(options.renderer);
+    gb.set_strict(options.strict);
+
+    let mut gdb = match options.gdb_port {
+        Some(port) => {
+            eprintln!("Waiting for gdb on 127.0.0.1:{}...", port);
+            match GdbStub::listen(("127.0.0.1", port)) {
+                Ok(stub) => Some(stub),
+                Err(e) => {
+                    eprintln!("gdb stub error on port {}: {}", port, e);
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        None => None,
+    };
+
+    let mut samples: Vec<i16> = Vec::new();
+    let mut frame_seen = false;
+    let mut outcome = None;
+    let mut frames_run = 0;
+
+    let mut frame = 0;
+    while frame < options.frames {
+        gb.set_buttons(held_at(&options.input, frame));
+        match gdb.as_mut().map(|stub| stub.run_frame(&mut gb)) {
+            None => frame_seen |= gb.run_frame(),
+            Some(GdbStatus::Running { frame_completed }) => frame_seen |= frame_completed,
+            // The frame has not run yet
+            Some(GdbStatus::Paused) => continue,
+            Some(GdbStatus::Detached) => {
+                eprintln!("gdb detached");
+                gdb = None;
+                continue;
+            }
+            Some(GdbStatus::Killed) => break,
+        }
+        samples.extend(gb.take_samples());
+        for access in gb.take_blocked() {
+            eprintln!("frame {}: {}", frame, access);
+        }
+        frames_run = frame + 1;
+        frame += 1;
+
+        let output = serial.borrow();
+        if let Some(text) = &options.fail_serial {
+            if contains(&output, text) {
+                outcome = Some(EXIT_FAIL);
+                break;
+            }
+        }
+        if let Some(text) = &options.until_serial {
+            if contains(&output, text) {
+                outcome = Some(EXIT_PASS);
+                break;
+            }
+        }
+        if let Some(expected) = options.until_hash {
+            if frame_seen && gb.framebuffer().hash() == expected {
+                outcome = Some(EXIT_PASS);
+                break;
+            }
+        }
+    }
+
+    // With the LCD off for the whole run no frame completes; this is the PPU's initial buffer
+    let framebuffer = gb.framebuffer();
+    let hash = framebuffer.hash();
+    println!("{:016x}", hash);
+
+    let output = serial.borrow();
+    if !output.is_empty() {
+        eprintln!("Serial output:\n{}", String::from_utf8_lossy(&output));
+    }
+    eprintln!("Ran {} frames", frames_run);
+
+    if let Some(path) = &options.png_path {
+        let png = png::encode_rgb(SCREEN_WIDTH, SCREEN_HEIGHT, &framebuffer.rgb_bytes());
+        if let Err(e) = std::fs::write(path, png) {
+            eprintln!("Failed to write PNG '{}': {}", path, e);
+            return EXIT_ERROR;
+        }
+    }
+    if let Some(path) = &options.wav_path {
+        if let Err(e) = std::fs::write(path, wav::encode_stereo(SAMPLE_RATE, &samples)) {
+            eprintln!("Failed to write WAV '{}': {}", path, e);
+            return EXIT_ERROR;
+        }
+    }
+
+    let waiting = options.until_serial.is_some() || options.until_hash.is_some();
+    let outcome = outcome.unwrap_or(if waiting { EXIT_TIMEOUT } else { EXIT_PASS });
+    if outcome == EXIT_PASS {
+        if let Some(expected) = options.expect_hash {
+            if hash != expected {
+                eprintln!(
+                    "Frame hash {:016x} does not match expected {:016x}",
+                    hash, expected
+                );
+                return EXIT_FAIL;
+            }
+        }
+    }
+    outcome
+}
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack
+        .windows(needle.len().max(1))
+        .any(|window| window == needle.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_script_takes_comments_holds_and_combined_buttons() {
+        let script = "# title screen\n\n120 start 5\n  300 A+b   # jump\n400 Up+LEFT 2\n";
+        let events = parse_input_script(script).unwrap();
+        let parsed: Vec<(u32, u32, u8)> = events
+            .iter()
+            .map(|event| (event.frame, event.hold, event.held))
+            .collect();
+        assert_eq!(parsed, [(120, 5, 0x80), (300, 1, 0x30), (400, 2, 0x06)]);
+    }
+
+    #[test]
+    fn held_at_covers_each_hold_and_merges_overlaps() {
+        let events = parse_input_script("10 a 3\n12 right\n").unwrap();
+        let held: Vec<u8> = (9..15).map(|frame| held_at(&events, frame)).collect();
+        assert_eq!(held, [0x00, 0x10, 0x10, 0x11, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn input_script_errors_name_the_line() {
+        let cases = [
+            (
+                "10 a\n# ok\n20 jump\n",
+                "input script line 3: unknown button 'jump'",
+            ),
+            ("x a\n", "input script line 1: invalid frame number"),
+            ("10 a -1\n", "input script line 1: invalid hold length"),
+            (
+                "\n10\n",
+                "input script line 2: expected '<frame> <buttons> [hold]'",
+            ),
+            (
+                "10 a 1 2\n",
+                "input script line 1: expected '<frame> <buttons> [hold]'",
+            ),
+        ];
+        for (script, message) in cases {
+            assert_eq!(parse_input_script(script).err().as_deref(), Some(message));
+        }
+    }
+}
@@ -0,0 +1,289 @@
+// Power-on — either run a boot ROM, or start where the boot ROM leaves off.
+//
+// The boot ROM is copyrighted and not shipped, so it is a runtime input. With
+// one, it is mapped over 0x0000–0x00FF (plus 0x0200–0x08FF for the CGB boot
+// ROM) until the game writes 0xFF50, and the CPU starts at 0x0000. Without
+// one, the machine starts at 0x0100 in the documented post-boot state of the
+// chosen model: CPU registers, IO registers, DIV and, on DMG/MGB, the
+// Nintendo logo left in VRAM.
+
+use crate::cpu::{Cpu, Registers};
+use crate::header::{CgbSupport, Header};
+use crate::memory::Memory;
+use std::fmt;
+
+const DMG_BIOS_SIZE: usize = 0x100;
+pub const CGB_BIOS_SIZE: usize = 0x900;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Dmg,
+    Mgb,
+    Cgb,
+}
+
+impl Model {
+    /// Look a model up by its lower-case name: "dmg", "mgb" or "cgb".
+    pub fn from_name(name: &str) -> Option<Model> {
+        match name {
+            "dmg" => Some(Model::Dmg),
+            "mgb" => Some(Model::Mgb),
+            "cgb" => Some(Model::Cgb),
+            _ => None,
+        }
+    }
+
+    /// The model to emulate when none is asked for: a CGB for cartridges
+    /// that support it, so they start in CGB mode, otherwise a DMG.
+    pub fn for_header(header: &Header) -> Model {
+        if header.cgb == CgbSupport::None {
+            Model::Dmg
+        } else {
+            Model::Cgb
+        }
+    }
+}
+
+/// How the machine starts.
+pub enum Boot {
+    /// Run this boot ROM (256 bytes for DMG/MGB, 2304 for CGB) from 0x0000.
+    Bios(Vec<u8>),
+    /// Start at 0x0100 in the state the boot ROM leaves this model in.
+    Skip(Model),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootError {
+    /// The boot ROM is neither DMG/MGB-sized nor CGB-sized.
+    InvalidBiosSize(usize),
+}
+
+impl fmt::Display for BootError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootError::InvalidBiosSize(size) => write!(
+                f,
+                "boot ROM is {} bytes (expected {} for DMG/MGB or {} for CGB)",
+                size, DMG_BIOS_SIZE, CGB_BIOS_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BootError {}
+
+/// Put a freshly created CPU and memory into their power-on state.
+pub fn power_on(boot: Boot, cpu: &mut Cpu, memory: &mut Memory) -> Result<(), BootError> {
+    match boot {
+        Boot::Bios(bios) => {
+            if bios.len() != DMG_BIOS_SIZE && bios.len() != CGB_BIOS_SIZE {
+                return Err(BootError::InvalidBiosSize(bios.len()));
+            }
+            memory.map_bios(bios);
+        }
+        Boot::Skip(model) => {
+            memory.skip_boot(model);
+            *cpu.registers_mut() = post_boot_registers(model, memory.header_checksum());
+        }
+    }
+    Ok(())
+}
+
+/// CPU registers at PC=0x0100. On DMG/MGB the boot ROM's header check leaves
+/// H and C set unless the header checksum byte (0x14D) is zero.
+fn post_boot_registers(model: Model, header_checksum: u8) -> Registers {
+    let (a, f, b, c, d, e, h, l) = match model {
+        Model::Dmg | Model::Mgb => (
+            if model == Model::Dmg { 0x01 } else { 0xFF },
+            if header_checksum == 0 { 0x80 } else { 0xB0 },
+            0x00,
+            0x13,
+            0x00,
+            0xD8,
+            0x01,
+            0x4D,
+        ),
+        Model::Cgb => (0x11, 0x80, 0x00, 0x00, 0xFF, 0x56, 0x00, 0x0D),
+    };
+    Registers {
+        a,
+        f,
+        b,
+        c,
+        d,
+        e,
+        h,
+        l,
+        program_counter: 0x0100,
+        stack_pointer: 0xFFFE,
+        ..Registers::default()
+    }
+}
+
+/// IO registers the boot ROM leaves with non-reset values, written in order.
+/// NR52 comes first so the APU accepts the rest. NR14 is written without
+/// its trigger bit: the boot chime has died away by 0x0100, and triggering
+/// channel 1 again would replay it.
+pub const POST_BOOT_IO: [(u16, u8); 27] = [
+    (0xFF26, 0xF1), // NR52
+    (0xFF00, 0x00), // P1: both rows selected, reads 0xCF
+    (0xFF07, 0xF8), // TAC
+    (0xFF0F, 0xE1), // IF
+    (0xFF10, 0x80), // NR10
+    (0xFF11, 0xBF), // NR11
+    (0xFF12, 0xF3), // NR12
+    (0xFF13, 0xFF), // NR13
+    (0xFF14, 0x3F), // NR14, reads 0xBF
+    (0xFF16, 0x3F), // NR21
+    (0xFF17, 0x00), // NR22
+    (0xFF18, 0xFF), // NR23
+    (0xFF19, 0x3F), // NR24, reads 0xBF
+    (0xFF1A, 0x7F), // NR30
+    (0xFF1B, 0xFF), // NR31
+    (0xFF1C, 0x9F), // NR32
+    (0xFF1D, 0xFF), // NR33
+    (0xFF1E, 0x3F), // NR34, reads 0xBF
+    (0xFF20, 0xFF), // NR41
+    (0xFF21, 0x00), // NR42
+    (0xFF22, 0x00), // NR43
+    (0xFF23, 0x3F), // NR44, reads 0xBF
+    (0xFF24, 0x77), // NR50
+    (0xFF25, 0xF3), // NR51
+    (0xFF40, 0x91), // LCDC
+    (0xFF47, 0xFC), // BGP
+    (0xFF50, 0x01), // boot ROM unmapped
+];
+
+/// Internal 16-bit DIV counter at 0x0100. DMG and MGB read DIV=0xAB; on CGB
+/// the boot ROM's run time depends on the cartridge header, so DIV varies.
+pub fn post_boot_div_counter(model: Model) -> u16 {
+    match model {
+        Model::Dmg | Model::Mgb => 0xABCC,
+        Model::Cgb => 0x0000,
+    }
+}
+
+/// VRAM writes that reproduce the logo the DMG/MGB boot ROM scrolls in: the
+/// 48-byte header logo at 0x0104 expanded to 24 tiles at 0x8010 (each bit
+/// doubled horizontally and vertically), the ® tile at 0x8190, and the tile
+/// map rows at 0x9904 and 0x9924 plus the ® at 0x9910.
+pub fn logo_vram(header_logo: &[u8]) -> Vec<(u16, u8)> {
+    const REGISTERED: [u8; 8] = [0x3C, 0x42, 0xB9, 0xA5, 0xB9, 0xA5, 0x42, 0x3C];
+    let mut writes = Vec::with_capacity(48 * 8 + 8 + 25);
+    let mut addr = 0x8010u16;
+    for &byte in header_logo {
+        for nibble in [byte >> 4, byte & 0x0F] {
+            let doubled = double_bits(nibble);
+            // Two rows, bitplane 0 only
+            writes.push((addr, doubled));
+            writes.push((addr + 2, doubled));
+            addr += 4;
+        }
+    }
+    for (row, &byte) in REGISTERED.iter().enumerate() {
+        writes.push((0x8190 + row as u16 * 2, byte));
+    }
+    writes.push((0x9910, 0x19));
+    for i in 0..12u8 {
+        writes.push((0x9904 + i as u16, i + 1));
+        writes.push((0x9924 + i as u16, i + 13));
+    }
+    writes
+}
+
+/// Widen a nibble to a byte by doubling every bit: 0b1010 → 0b11001100.
+fn double_bits(nibble: u8) -> u8 {
+    (0..4).fold(0, |out, bit| {
+        if nibble & (1 << bit) != 0 {
+            out | (0b11 << (bit * 2))
+        } else {
+            out
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryAccess;
+
+    /// A, F, B, C, D, E, H, L.
+    fn registers(r: &Registers) -> [u8; 8] {
+        [r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l]
+    }
+
+    /// A DMG past its boot ROM, with the Nintendo logo's first two bytes
+    /// (0xCE 0xED) in the header.
+    fn booted() -> (Cpu, Memory) {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0104] = 0xCE;
+        rom[0x0105] = 0xED;
+        let mut memory = Memory::initialize_with_rom(rom).unwrap();
+        let mut cpu = Cpu::initialize();
+        power_on(Boot::Skip(Model::Dmg), &mut cpu, &mut memory).unwrap();
+        (cpu, memory)
+    }
+
+    #[test]
+    fn post_boot_registers_match_each_model() {
+        let expected = [
+            (Model::Dmg, [0x01, 0xB0, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D]),
+            (Model::Mgb, [0xFF, 0xB0, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D]),
+            (Model::Cgb, [0x11, 0x80, 0x00, 0x00, 0xFF, 0x56, 0x00, 0x0D]),
+        ];
+        for (model, values) in expected {
+            let r = post_boot_registers(model, 0x42);
+            assert_eq!(registers(&r), values, "{:?}", model);
+            assert_eq!((r.program_counter, r.stack_pointer), (0x0100, 0xFFFE));
+        }
+    }
+
+    #[test]
+    fn zero_header_checksum_leaves_h_and_c_clear() {
+        assert_eq!(post_boot_registers(Model::Dmg, 0x00).f, 0x80);
+        assert_eq!(post_boot_registers(Model::Mgb, 0x00).f, 0x80);
+        assert_eq!(post_boot_registers(Model::Cgb, 0x00).f, 0x80);
+
+        let (cpu, _) = booted();
+        assert_eq!(cpu.registers().f, 0x80);
+    }
+
+    #[test]
+    fn skipped_boot_leaves_the_io_registers_set() {
+        let (_, memory) = booted();
+        assert_eq!(memory.read_byte(0xFF04), 0xAB);
+        assert_eq!(memory.read_byte(0xFF40), 0x91);
+        assert_eq!(memory.read_byte(0xFF47), 0xFC);
+        assert_eq!(memory.read_byte(0xFF00), 0xCF);
+    }
+
+    #[test]
+    fn skipped_boot_unmaps_the_boot_rom() {
+        let mut memory = Memory::initialize_with_rom(vec![0; 0x8000]).unwrap();
+        memory.map_bios(vec![0x31; DMG_BIOS_SIZE]);
+        assert_eq!(memory.read_byte(0x0000), 0x31);
+        memory.skip_boot(Model::Dmg);
+        assert_eq!(memory.read_byte(0x0000), 0x00);
+    }
+
+    #[test]
+    fn logo_is_left_in_vram_with_its_tile_map() {
+        let (_, memory) = booted();
+        // 0xC -> 0b11110000 and 0xE -> 0b11111100, each on two rows
+        for (addr, value) in [
+            (0x8010, 0xF0),
+            (0x8012, 0xF0),
+            (0x8014, 0xFC),
+            (0x8016, 0xFC),
+        ] {
+            assert_eq!(memory.read_byte(addr), value, "{:04X}", addr);
+        }
+        for i in 0..12 {
+            assert_eq!(memory.read_byte(0x9904 + i), i as u8 + 1);
+            assert_eq!(memory.read_byte(0x9924 + i), i as u8 + 13);
+        }
+        assert_eq!(memory.read_byte(0x9910), 0x19);
+        assert_eq!(memory.read_byte(0x9903), 0x00);
+        assert_eq!(memory.read_byte(0x9930), 0x00);
+    }
+}
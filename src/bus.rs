@@ -0,0 +1,462 @@
+// System bus — the CPU's view of memory, clocking the rest of the machine as
+// instructions run.
+//
+// Every CPU memory access takes one M-cycle (4 T-cycles). The bus counts those
+// cycles and hands them to the PPU, APU, timer and serial port, so a register
+// write lands on the cycle it happens on rather than after the whole
+// instruction. Instructions mark the M-cycles they spend without touching
+// memory with `idle_cycle` where they happen, so a push or call writes the
+// stack after its internal delay, as on hardware. Anything left over is
+// handed over when the step finishes.
+//
+// Catching up is lazy: cycles accumulate in `pending` and are only applied
+// when the CPU touches an address the other blocks can see or change (VRAM,
+// OAM, IO registers, IE) and at the end of each step. Accesses to ROM, WRAM
+// and HRAM — the vast majority — only bump a counter. While OAM DMA runs it
+// can see every bus, so then each access is caught up.
+//
+// `memory` is public for untimed access (debug views, save states, the CPU's
+// own bookkeeping reads); only the `read_*`/`write_*` methods here advance time.
+//
+// The CPU's accesses are also the ones the PPU can lock out: while it reads
+// OAM (mode 2) or VRAM and OAM (mode 3) the CPU sees 0xFF and its writes are
+// lost. In strict mode each blocked access is recorded with the PC of the
+// instruction that made it, for finding unsafe VRAM and OAM writes.
+//
+// Reads and writes are also where the debugger's watchpoints fire: accesses
+// that fall in a watched range are recorded until the debugger collects them
+// after the instruction.
+
+use crate::cpu::{self, TimeIncrement};
+use crate::debugger::{Access, WatchHit, Watchpoint};
+use crate::gpu::{Framebuffer, Gpu};
+use crate::memory::MemoryAccess;
+use std::fmt;
+
+// T-cycles per memory access
+const ACCESS_CYCLES: u32 = 4;
+// Longest stretch of paused CPU time handed over at once, so the APU, which
+// produces at most one sample per call, does not drop any
+const STALL_CHUNK: u32 = 32;
+
+pub struct Bus {
+    pub memory: Box<dyn MemoryAccess>,
+    pub gpu: Gpu,
+    // T-cycles the CPU has spent that the rest of the system has not seen yet
+    pending: u32,
+    // T-cycles spent on memory accesses and idle cycles so far in the current step
+    step_cycles: u32,
+    // Frame finished by the PPU, until picked up with `take_frame`
+    frame: Option<Framebuffer>,
+    // Stereo APU output, interleaved [L, R, L, R, ...], until `take_samples`
+    samples: Vec<i16>,
+    // Address of the instruction being executed
+    pc: u16,
+    // OAM DMA is under way: every access is caught up, so the DMA has got
+    // exactly as far as the CPU when the access is checked against it
+    oam_dma: bool,
+    // Record blocked accesses in `blocked` until `take_blocked`
+    strict: bool,
+    blocked: Vec<BlockedAccess>,
+    // Read and write watchpoints; accesses that hit one wait in `watch_hits`
+    watchpoints: Vec<Watchpoint>,
+    watch_hits: Vec<WatchHit>,
+}
+
+/// A CPU access to VRAM or OAM that the PPU blocked, recorded in strict mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockedAccess {
+    /// Address of the instruction that made the access.
+    pub pc: u16,
+    pub addr: u16,
+    /// The value written, or None for a read.
+    pub write: Option<u8>,
+    /// PPU mode at the time: 2 (OAM scan) or 3 (drawing).
+    pub mode: u8,
+}
+
+impl fmt::Display for BlockedAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.write {
+            Some(value) => write!(
+                f,
+                "PC=0x{:04X}: write 0x{:02X} to 0x{:04X} blocked in mode {}",
+                self.pc, value, self.addr, self.mode
+            ),
+            None => write!(
+                f,
+                "PC=0x{:04X}: read from 0x{:04X} blocked in mode {}",
+                self.pc, self.addr, self.mode
+            ),
+        }
+    }
+}
+
+impl Bus {
+    pub fn new(memory: Box<dyn MemoryAccess>, gpu: Gpu) -> Self {
+        Self {
+            memory,
+            gpu,
+            pending: 0,
+            step_cycles: 0,
+            frame: None,
+            samples: Vec::with_capacity(4096),
+            pc: 0,
+            oam_dma: false,
+            strict: false,
+            blocked: Vec::new(),
+            watchpoints: Vec::new(),
+            watch_hits: Vec::new(),
+        }
+    }
+
+    /// CPU read: one M-cycle, then the byte as the rest of the system sees it
+    /// at that point (0xFF if the PPU has the address locked).
+    pub fn read_byte(&mut self, addr: u16) -> u8 {
+        self.access_cycle(addr);
+        let value = self.read_visible(addr);
+        if !self.watchpoints.is_empty() {
+            self.watch(addr, Access::Read, value);
+        }
+        value
+    }
+
+    /// The byte the CPU sees at `addr` right now.
+    fn read_visible(&mut self, addr: u16) -> u8 {
+        if self.oam_dma {
+            if let Some(value) = self.memory.oam_dma_conflict(addr) {
+                return value;
+            }
+        }
+        if self.ppu_blocks(addr, None) {
+            return 0xFF;
+        }
+        self.memory.read_byte(addr)
+    }
+
+    /// CPU write: one M-cycle, then the write, unless the PPU has the address locked.
+    pub fn write_byte(&mut self, addr: u16, value: u8) {
+        self.access_cycle(addr);
+        if !self.watchpoints.is_empty() {
+            self.watch(addr, Access::Write, value);
+        }
+        if self.oam_dma && self.memory.oam_dma_conflict(addr).is_some() {
+            return;
+        }
+        if self.ppu_blocks(addr, Some(value)) {
+            return;
+        }
+        self.memory.write_byte(addr, value);
+    }
+
+    /// Two CPU reads, low byte first.
+    pub fn read_word(&mut self, addr: u16) -> u16 {
+        let lo = self.read_byte(addr) as u16;
+        let hi = self.read_byte(addr.wrapping_add(1)) as u16;
+        (hi << 8) | lo
+    }
+
+    /// Two CPU writes, low byte first.
+    pub fn write_word(&mut self, addr: u16, value: u16) {
+        self.write_byte(addr, value as u8);
+        self.write_byte(addr.wrapping_add(1), (value >> 8) as u8);
+    }
+
+    /// An M-cycle the CPU spends without a memory access, such as the
+    /// internal delay before a push. Counts like an access to CPU-only memory.
+    pub fn idle_cycle(&mut self) {
+        self.pending += ACCESS_CYCLES;
+        self.step_cycles += ACCESS_CYCLES;
+    }
+
+    /// End a CPU step (an instruction, an idle HALT cycle or an interrupt
+    /// dispatch) that took `cycles` T-cycles in total: hand over whatever its
+    /// memory accesses and idle cycles have not already accounted for and
+    /// catch up.
+    pub fn finish_step(&mut self, cycles: u32) {
+        debug_assert!(self.step_cycles <= cycles, "step overran its cycle count");
+        self.pending += cycles.saturating_sub(self.step_cycles);
+        self.step_cycles = 0;
+        self.catch_up();
+    }
+
+    /// Note the address of the instruction about to run, for strict-mode reports.
+    pub fn begin_instruction(&mut self, pc: u16) {
+        self.pc = pc;
+    }
+
+    /// Turn recording of blocked VRAM and OAM accesses on or off.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+        self.blocked.clear();
+    }
+
+    /// Accesses blocked since the last call, oldest first (strict mode only).
+    pub fn take_blocked(&mut self) -> Vec<BlockedAccess> {
+        std::mem::take(&mut self.blocked)
+    }
+
+    /// Watch these ranges for reads and writes, replacing any watched before.
+    pub fn set_watchpoints(&mut self, watchpoints: Vec<Watchpoint>) {
+        self.watchpoints = watchpoints;
+        self.watch_hits.clear();
+    }
+
+    /// Watched accesses since the last call, oldest first.
+    pub fn take_watch_hits(&mut self) -> Vec<WatchHit> {
+        std::mem::take(&mut self.watch_hits)
+    }
+
+    /// The frame the PPU finished during the last step, if any.
+    pub fn take_frame(&mut self) -> Option<Framebuffer> {
+        self.frame.take()
+    }
+
+    /// Audio produced since the last call, interleaved stereo.
+    pub fn take_samples(&mut self) -> Vec<i16> {
+        std::mem::take(&mut self.samples)
+    }
+
+    fn access_cycle(&mut self, addr: u16) {
+        self.pending += ACCESS_CYCLES;
+        self.step_cycles += ACCESS_CYCLES;
+        if self.oam_dma || observable(addr) {
+            self.catch_up();
+        }
+    }
+
+    /// Whether the PPU locks the CPU out of `addr` right now, recording the
+    /// access in strict mode. Only called once `addr` has been caught up.
+    fn ppu_blocks(&mut self, addr: u16, write: Option<u8>) -> bool {
+        if !matches!(addr, 0x8000..=0x9FFF | 0xFE00..=0xFE9F) || !self.memory.ppu_blocks(addr) {
+            return false;
+        }
+        if self.strict {
+            self.blocked.push(BlockedAccess {
+                pc: self.pc,
+                addr,
+                write,
+                mode: self.memory.read_byte(0xFF41) & 0x03,
+            });
+        }
+        true
+    }
+
+    /// Record an access if a watchpoint covers it. The CPU fetching the
+    /// current instruction's own bytes is not a data read.
+    fn watch(&mut self, addr: u16, access: Access, value: u8) {
+        if !self.watchpoints.iter().any(|w| w.hits(addr, access)) {
+            return;
+        }
+        if access == Access::Read {
+            let length = cpu::instruction_length(self.memory.read_byte(self.pc));
+            if addr.wrapping_sub(self.pc) < length {
+                return;
+            }
+        }
+        self.watch_hits.push(WatchHit {
+            pc: self.pc,
+            addr,
+            access,
+            value,
+        });
+    }
+
+    /// Advance the PPU, APU, timer and serial port by the pending cycles,
+    /// then by any cycles the CPU was paused for (HDMA, speed switch).
+    fn catch_up(&mut self) {
+        let t = std::mem::take(&mut self.pending);
+        if t > 0 {
+            self.advance(t);
+        }
+        loop {
+            let mut stall = self.memory.take_stall_cycles();
+            if stall == 0 {
+                break;
+            }
+            while stall > 0 {
+                let t = stall.min(STALL_CHUNK);
+                self.advance(t);
+                stall -= t;
+            }
+        }
+        self.oam_dma = self.memory.oam_dma_active();
+    }
+
+    /// Clock everything but the CPU by `t` CPU T-cycles. At double speed the
+    /// timer and serial port keep pace with the CPU while the PPU and APU run
+    /// at half its rate.
+    fn advance(&mut self, t: u32) {
+        self.memory.tick_io(t);
+        let t = if self.memory.double_speed() { t / 2 } else { t };
+        if let Some((l, r)) = self.memory.tick_apu_sample(t) {
+            self.samples.push(l);
+            self.samples.push(r);
+        }
+        let time_increment = TimeIncrement {
+            m: (t / 4) as u8,
+            t: t as u8,
+        };
+        if let Some(framebuffer) = self.gpu.step(time_increment, &mut self.memory) {
+            self.frame = Some(framebuffer);
+        }
+    }
+}
+
+/// Whether the PPU, APU, timer or serial port can see or change `addr`, so
+/// they must be caught up before the CPU touches it: VRAM, OAM, the IO
+/// registers and IE. ROM, cartridge RAM, WRAM and HRAM are CPU-only.
+fn observable(addr: u16) -> bool {
+    matches!(addr, 0x8000..=0x9FFF | 0xFE00..=0xFF7F | 0xFFFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::Cpu;
+    use crate::memory::Memory;
+
+    // Programs run from WRAM, clear of the BIOS overlay
+    const PROGRAM: u16 = 0xC000;
+
+    /// A CPU about to run `program` with the stack just above DIV, so the
+    /// last byte a push writes resets it. TIMA counts every 16 T-cycles.
+    fn setup(program: &[u8]) -> (Cpu, Bus) {
+        let memory = Memory::initialize_with_rom(vec![0; 0x8000]).unwrap();
+        let mut bus = Bus::new(Box::new(memory), Gpu::initialize());
+        for (i, byte) in program.iter().enumerate() {
+            bus.memory.write_byte(PROGRAM + i as u16, *byte);
+        }
+        bus.memory.write_byte(0xFF07, 0x05);
+        let mut cpu = Cpu::initialize();
+        cpu.registers_mut().program_counter = PROGRAM;
+        cpu.registers_mut().stack_pointer = 0xFF06;
+        (cpu, bus)
+    }
+
+    /// Run NOPs until the PPU is in `mode`, with the LCD on.
+    fn run_until_mode(cpu: &mut Cpu, bus: &mut Bus, mode: u8) {
+        bus.memory.write_byte(0xFF40, 0x80);
+        while bus.memory.read_byte(0xFF41) & 0x03 != mode {
+            cpu.registers_mut().program_counter = PROGRAM + 0x10;
+            cpu.step(bus);
+        }
+    }
+
+    /// Write `value` to `addr` with LD (HL),A and read it back with LD A,(HL),
+    /// as `setup(&[0x77, 0x7E])` runs them.
+    fn write_then_read(cpu: &mut Cpu, bus: &mut Bus, addr: u16, value: u8) -> u8 {
+        let registers = cpu.registers_mut();
+        registers.a = value;
+        registers.h = (addr >> 8) as u8;
+        registers.l = addr as u8;
+        registers.program_counter = PROGRAM;
+        cpu.step(bus);
+        cpu.step(bus);
+        cpu.registers().a
+    }
+
+    /// T-cycles since DIV was reset, modulo 16, told by how many NOPs it
+    /// takes TIMA to tick.
+    fn div_phase(cpu: &mut Cpu, bus: &mut Bus) -> u32 {
+        let tima = bus.memory.read_byte(0xFF05);
+        let mut phase = 16;
+        while bus.memory.read_byte(0xFF05) == tima {
+            cpu.registers_mut().program_counter = PROGRAM + 0x10;
+            cpu.step(bus);
+            phase -= 4;
+        }
+        phase
+    }
+
+    #[test]
+    fn idle_cycles_reach_the_timer_before_the_next_access() {
+        let (_, mut bus) = setup(&[]);
+        bus.idle_cycle();
+        bus.idle_cycle();
+        bus.write_byte(0xFF04, 0);
+        bus.finish_step(12);
+        bus.write_byte(0xFF05, 0);
+        assert_eq!(bus.memory.read_byte(0xFF05), 0);
+        bus.idle_cycle();
+        bus.idle_cycle();
+        bus.idle_cycle();
+        // 20 T-cycles since the reset by this read: TIMA ticked at 16
+        assert_eq!(bus.read_byte(0xFF05), 1);
+    }
+
+    #[test]
+    fn stack_writes_land_after_the_internal_cycle_high_byte_first() {
+        let programs: [&[u8]; 3] = [
+            &[0xC5],             // PUSH BC
+            &[0xCD, 0x10, 0xC0], // CALL C010
+            &[0xFF],             // RST 38
+        ];
+        for program in programs {
+            let (mut cpu, mut bus) = setup(program);
+            cpu.registers_mut().b = 0x12;
+            cpu.registers_mut().c = 0x00;
+            cpu.step(&mut bus);
+            // The low byte resets DIV on the instruction's last M-cycle
+            assert_eq!(cpu.registers().stack_pointer, 0xFF04, "{:02X?}", program);
+            assert_eq!(div_phase(&mut cpu, &mut bus), 0, "{:02X?}", program);
+        }
+    }
+
+    #[test]
+    fn interrupt_dispatch_pushes_after_its_wait_states() {
+        let (mut cpu, mut bus) = setup(&[]);
+        cpu.ime = true;
+        bus.memory.write_byte(0xFFFF, 0x01);
+        bus.memory.write_byte(0xFF0F, 0x01);
+        assert_eq!(cpu.handle_interrupts(&mut bus).t, 20);
+        assert_eq!(cpu.registers().program_counter, 0x0040);
+        // Two wait states and the high byte, then DIV reset, then the jump
+        assert_eq!(div_phase(&mut cpu, &mut bus), 4);
+    }
+
+    #[test]
+    fn ppu_blocks_vram_and_oam_during_mode_3() {
+        let (mut cpu, mut bus) = setup(&[0x77, 0x7E]);
+        bus.set_strict(true);
+        run_until_mode(&mut cpu, &mut bus, 3);
+        assert_eq!(write_then_read(&mut cpu, &mut bus, 0x8000, 0x42), 0xFF);
+        assert_eq!(bus.memory.read_byte(0x8000), 0x00);
+        let blocked = bus.take_blocked();
+        assert_eq!(blocked.len(), 2);
+        assert_eq!(blocked[0].pc, PROGRAM);
+        assert_eq!(blocked[0].write, Some(0x42));
+        assert_eq!(blocked[1].pc, PROGRAM + 1);
+        assert_eq!(blocked[1].write, None);
+        assert_eq!(blocked[1].mode, 3);
+
+        assert_eq!(write_then_read(&mut cpu, &mut bus, 0xFE00, 0x42), 0xFF);
+        assert_eq!(bus.memory.read_byte(0xFE00), 0x00);
+    }
+
+    #[test]
+    fn ppu_blocks_only_oam_during_mode_2() {
+        let (mut cpu, mut bus) = setup(&[0x77, 0x7E]);
+        bus.set_strict(true);
+        run_until_mode(&mut cpu, &mut bus, 2);
+        assert_eq!(write_then_read(&mut cpu, &mut bus, 0xFE00, 0x42), 0xFF);
+        assert_eq!(bus.memory.read_byte(0xFE00), 0x00);
+        assert_eq!(bus.take_blocked()[0].mode, 2);
+        assert_eq!(write_then_read(&mut cpu, &mut bus, 0x8000, 0x42), 0x42);
+        assert!(bus.take_blocked().is_empty());
+    }
+
+    #[test]
+    fn vram_and_oam_are_open_in_hblank_and_vblank() {
+        for mode in [0, 1] {
+            let (mut cpu, mut bus) = setup(&[0x77, 0x7E]);
+            bus.set_strict(true);
+            run_until_mode(&mut cpu, &mut bus, mode);
+            for addr in [0x8000, 0xFE00] {
+                assert_eq!(write_then_read(&mut cpu, &mut bus, addr, 0x42), 0x42);
+                assert_eq!(bus.memory.read_byte(addr), 0x42);
+            }
+            assert!(bus.take_blocked().is_empty(), "mode {}", mode);
+        }
+    }
+}
@@ -0,0 +1,632 @@
+// Cartridge memory bank controllers.
+//
+// `Memory` forwards the cartridge's address ranges to an `Mbc`:
+//   0x0000–0x7FFF  ROM reads, and writes to the mapper's control registers
+//   0xA000–0xBFFF  external RAM (or mapper-specific registers such as the MBC3 RTC)
+//
+// Mappers are picked from the cartridge type byte at header 0x147.
+
+use crate::header::Header;
+use crate::mbc3::{Mbc3, TimeSource};
+use crate::savestate::{StateError, StateReader, StateWriter};
+use std::fmt;
+
+pub trait Mbc {
+    /// Read from 0x0000–0x7FFF.
+    fn read_rom(&self, addr: u16) -> u8;
+    /// Write to 0x0000–0x7FFF (mapper control registers).
+    fn write_rom(&mut self, addr: u16, value: u8);
+    /// Read from 0xA000–0xBFFF.
+    fn read_ram(&self, addr: u16) -> u8;
+    /// Write to 0xA000–0xBFFF.
+    fn write_ram(&mut self, addr: u16, value: u8);
+    /// Battery-backed contents in the `.sav` layout shared by other emulators:
+    /// the raw RAM, followed by a clock block for carts with an RTC.
+    fn save_data(&self) -> Vec<u8>;
+    /// Restore contents produced by `save_data` (or another emulator's `.sav`).
+    fn load_save_data(&mut self, data: &[u8]);
+    /// Whether the cartridge's rumble motor is currently driven (MBC5 rumble carts).
+    fn rumble(&self) -> bool {
+        false
+    }
+    /// Replace the wall clock driving the cartridge's real-time clock, if it has one.
+    fn set_rtc_time_source(&mut self, source: Box<dyn TimeSource>) {
+        let _ = source;
+    }
+    /// Write mapper registers and RAM into a save state. ROM is not included.
+    fn save_state(&self, w: &mut StateWriter);
+    /// Restore mapper registers and RAM from a save state.
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CartridgeError {
+    /// The ROM file could not be read.
+    Io(String),
+    /// The file is too short to hold a cartridge header.
+    TooSmall(usize),
+    /// Header byte 0x148 is not a known ROM size code.
+    InvalidRomSize(u8),
+    /// Header byte 0x149 is not a known RAM size code.
+    InvalidRamSize(u8),
+    /// The file is shorter than the ROM size the header declares.
+    Truncated { expected: usize, actual: usize },
+    /// Header byte 0x147 names a mapper this emulator does not implement.
+    UnsupportedType(u8),
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CartridgeError::Io(reason) => write!(f, "{}", reason),
+            CartridgeError::TooSmall(len) => write!(
+                f,
+                "file is {} bytes, too small for a Game Boy ROM (the header ends at 0x150)",
+                len
+            ),
+            CartridgeError::InvalidRomSize(code) => {
+                write!(f, "invalid ROM size code 0x{:02X} in the header", code)
+            }
+            CartridgeError::InvalidRamSize(code) => {
+                write!(f, "invalid RAM size code 0x{:02X} in the header", code)
+            }
+            CartridgeError::Truncated { expected, actual } => write!(
+                f,
+                "ROM is truncated: the header declares {} KB but the file is {} bytes",
+                expected / 1024,
+                actual
+            ),
+            CartridgeError::UnsupportedType(code) => {
+                write!(f, "unsupported cartridge type 0x{:02X}", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {}
+
+/// Build the mapper for `rom` from its parsed header.
+pub fn load(rom: Vec<u8>, header: &Header) -> Result<Box<dyn Mbc>, CartridgeError> {
+    let cart_type = header.cartridge_type;
+    let ram = vec![0; header.ram_size];
+    let mbc: Box<dyn Mbc> = match cart_type {
+        0x00 | 0x08 | 0x09 => Box::new(RomOnly { rom, ram }),
+        0x01..=0x03 => Box::new(Mbc1::new(rom, ram)),
+        0x05 | 0x06 => Box::new(Mbc2::new(rom)),
+        0x0F..=0x13 => Box::new(Mbc3::new(rom, ram)),
+        0x19..=0x1E => Box::new(Mbc5::new(rom, ram, cart_type >= 0x1C)),
+        other => return Err(CartridgeError::UnsupportedType(other)),
+    };
+    Ok(mbc)
+}
+
+/// Copy as much of `data` as fits into `ram`.
+pub(crate) fn load_ram(ram: &mut [u8], data: &[u8]) {
+    let len = ram.len().min(data.len());
+    ram[..len].copy_from_slice(&data[..len]);
+}
+
+/// Read `addr` (0x0000–0x3FFF or 0x4000–0x7FFF) from ROM bank `bank`,
+/// wrapping the bank number to the ROM size.
+pub(crate) fn read_banked_rom(rom: &[u8], bank: usize, addr: u16) -> u8 {
+    let banks = (rom.len() / 0x4000).max(2);
+    let offset = (bank % banks) * 0x4000 + (addr as usize & 0x3FFF);
+    rom.get(offset).copied().unwrap_or(0xFF)
+}
+
+/// Offset into `ram` for 0xA000–0xBFFF in RAM bank `bank`, wrapping to the RAM size.
+pub(crate) fn banked_ram_offset(ram: &[u8], bank: usize, addr: u16) -> usize {
+    (bank * 0x2000 + (addr as usize - 0xA000)) % ram.len()
+}
+
+// ---------------------------------------------------------------------------
+// No mapper (32KB ROM, optional 8KB RAM)
+// ---------------------------------------------------------------------------
+
+struct RomOnly {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+}
+
+impl Mbc for RomOnly {
+    fn read_rom(&self, addr: u16) -> u8 {
+        self.rom.get(addr as usize).copied().unwrap_or(0xFF)
+    }
+
+    fn write_rom(&mut self, _addr: u16, _value: u8) {}
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if self.ram.is_empty() {
+            return 0xFF;
+        }
+        self.ram[banked_ram_offset(&self.ram, 0, addr)]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) {
+        if !self.ram.is_empty() {
+            let offset = banked_ram_offset(&self.ram, 0, addr);
+            self.ram[offset] = value;
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        load_ram(&mut self.ram, data);
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        r.bytes_into(&mut self.ram)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MBC1 (up to 2MB ROM, 32KB RAM)
+// ---------------------------------------------------------------------------
+
+struct Mbc1 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    // 0x2000-0x3FFF: lower 5 bits of the ROM bank (0 is translated to 1)
+    bank1: u8,
+    // 0x4000-0x5FFF: RAM bank, or bits 5-6 of the ROM bank on large carts
+    bank2: u8,
+    // 0x6000-0x7FFF: 0 = simple banking, 1 = bank2 also applies to 0x0000-0x3FFF and RAM
+    banking_mode: u8,
+    // MBC1M multicart: bank1 is wired as 4 bits, so bank2 selects 256KB games
+    multicart: bool,
+}
+
+impl Mbc1 {
+    fn new(rom: Vec<u8>, ram: Vec<u8>) -> Self {
+        let multicart = is_mbc1_multicart(&rom);
+        Mbc1 {
+            rom,
+            ram,
+            ram_enabled: false,
+            bank1: 1,
+            bank2: 0,
+            banking_mode: 0,
+            multicart,
+        }
+    }
+
+    /// Number of bits bank1 contributes to the ROM bank number.
+    fn bank1_bits(&self) -> u8 {
+        if self.multicart {
+            4
+        } else {
+            5
+        }
+    }
+
+    fn ram_bank(&self) -> usize {
+        if self.banking_mode == 1 {
+            self.bank2 as usize
+        } else {
+            0
+        }
+    }
+}
+
+/// MBC1M multicarts are 1MB boards whose bank 0x10 (the second game's bank 0)
+/// carries its own Nintendo logo.
+fn is_mbc1_multicart(rom: &[u8]) -> bool {
+    const LOGO: std::ops::Range<usize> = 0x0104..0x0134;
+    const SECOND_GAME: usize = 0x10 * 0x4000;
+    rom.len() == 0x100000 && rom[LOGO] == rom[SECOND_GAME + LOGO.start..SECOND_GAME + LOGO.end]
+}
+
+impl Mbc for Mbc1 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = if addr < 0x4000 {
+            // Bank 0, or bank2 << 5 in mode 1
+            if self.banking_mode == 1 {
+                (self.bank2 as usize) << self.bank1_bits()
+            } else {
+                0
+            }
+        } else {
+            let mask = (1u8 << self.bank1_bits()) - 1;
+            ((self.bank2 as usize) << self.bank1_bits()) | (self.bank1 & mask) as usize
+        };
+        read_banked_rom(&self.rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            // RAM enable: 0x0A in the low nibble enables, anything else disables
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            // ROM bank, lower 5 bits; 0 is translated to 1 before masking
+            0x2000..=0x3FFF => {
+                let bank = value & 0x1F;
+                self.bank1 = if bank == 0 { 1 } else { bank };
+            }
+            // RAM bank or upper ROM bank bits
+            0x4000..=0x5FFF => self.bank2 = value & 0x03,
+            // Banking mode select
+            _ => self.banking_mode = value & 0x01,
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return 0xFF;
+        }
+        self.ram[banked_ram_offset(&self.ram, self.ram_bank(), addr)]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) {
+        if self.ram_enabled && !self.ram.is_empty() {
+            let offset = banked_ram_offset(&self.ram, self.ram_bank(), addr);
+            self.ram[offset] = value;
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        load_ram(&mut self.ram, data);
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bool(self.ram_enabled);
+        w.u8(self.bank1);
+        w.u8(self.bank2);
+        w.u8(self.banking_mode);
+        w.bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ram_enabled = r.bool()?;
+        self.bank1 = r.u8()? & 0x1F;
+        self.bank2 = r.u8()? & 0x03;
+        self.banking_mode = r.u8()? & 0x01;
+        r.bytes_into(&mut self.ram)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MBC2 (up to 256KB ROM, built-in 512×4-bit RAM)
+// ---------------------------------------------------------------------------
+
+struct Mbc2 {
+    rom: Vec<u8>,
+    // Only the low nibble of each byte exists
+    ram: [u8; 512],
+    ram_enabled: bool,
+    rom_bank: u8,
+}
+
+impl Mbc2 {
+    fn new(rom: Vec<u8>) -> Self {
+        Mbc2 {
+            rom,
+            ram: [0; 512],
+            ram_enabled: false,
+            rom_bank: 1,
+        }
+    }
+}
+
+impl Mbc for Mbc2 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = if addr < 0x4000 {
+            0
+        } else {
+            self.rom_bank as usize
+        };
+        read_banked_rom(&self.rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        // Both registers live in 0x0000-0x3FFF; address bit 8 picks which one
+        if addr >= 0x4000 {
+            return;
+        }
+        if addr & 0x0100 == 0 {
+            self.ram_enabled = value & 0x0F == 0x0A;
+        } else {
+            let bank = value & 0x0F;
+            self.rom_bank = if bank == 0 { 1 } else { bank };
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        // 512 nibbles echoed across 0xA000-0xBFFF; the upper nibble reads as 1s
+        0xF0 | self.ram[(addr & 0x01FF) as usize]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) {
+        if self.ram_enabled {
+            self.ram[(addr & 0x01FF) as usize] = value & 0x0F;
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.to_vec()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        load_ram(&mut self.ram, data);
+        for nibble in self.ram.iter_mut() {
+            *nibble &= 0x0F;
+        }
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bool(self.ram_enabled);
+        w.u8(self.rom_bank);
+        w.bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ram_enabled = r.bool()?;
+        self.rom_bank = r.u8()? & 0x0F;
+        r.bytes_into(&mut self.ram)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MBC5 (up to 8MB ROM, 128KB RAM, optional rumble motor)
+// ---------------------------------------------------------------------------
+
+struct Mbc5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    // 9-bit ROM bank: 0x2000-0x2FFF holds bits 0-7, 0x3000-0x3FFF bit 8.
+    // Unlike MBC1, bank 0 can be mapped at 0x4000.
+    rom_bank: u16,
+    ram_bank: u8,
+    // Rumble carts wire RAM bank bit 3 to the motor instead
+    has_rumble: bool,
+    rumble: bool,
+}
+
+impl Mbc5 {
+    fn new(rom: Vec<u8>, ram: Vec<u8>, has_rumble: bool) -> Self {
+        Mbc5 {
+            rom,
+            ram,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            has_rumble,
+            rumble: false,
+        }
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = if addr < 0x4000 {
+            0
+        } else {
+            self.rom_bank as usize
+        };
+        read_banked_rom(&self.rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0xFF) | (((value & 0x01) as u16) << 8)
+            }
+            0x4000..=0x5FFF => {
+                if self.has_rumble {
+                    self.rumble = value & 0x08 != 0;
+                    self.ram_bank = value & 0x07;
+                } else {
+                    self.ram_bank = value & 0x0F;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return 0xFF;
+        }
+        self.ram[banked_ram_offset(&self.ram, self.ram_bank as usize, addr)]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) {
+        if self.ram_enabled && !self.ram.is_empty() {
+            let offset = banked_ram_offset(&self.ram, self.ram_bank as usize, addr);
+            self.ram[offset] = value;
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        self.ram.clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        load_ram(&mut self.ram, data);
+    }
+
+    fn rumble(&self) -> bool {
+        self.rumble
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bool(self.ram_enabled);
+        w.u16(self.rom_bank);
+        w.u8(self.ram_bank);
+        w.bool(self.rumble);
+        w.bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ram_enabled = r.bool()?;
+        self.rom_bank = r.u16()? & 0x1FF;
+        self.ram_bank = r.u8()? & 0x0F;
+        self.rumble = r.bool()?;
+        r.bytes_into(&mut self.ram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ROM of `banks` 16KB banks, each starting with its own bank number
+    /// (low byte, then high byte).
+    fn numbered_rom(banks: usize) -> Vec<u8> {
+        let mut rom = vec![0; banks * 0x4000];
+        for bank in 0..banks {
+            rom[bank * 0x4000] = bank as u8;
+            rom[bank * 0x4000 + 1] = (bank >> 8) as u8;
+        }
+        rom
+    }
+
+    /// The bank number mapped at `addr` (0x0000 or 0x4000).
+    fn bank_at(mbc: &dyn Mbc, addr: u16) -> usize {
+        mbc.read_rom(addr) as usize | (mbc.read_rom(addr + 1) as usize) << 8
+    }
+
+    #[test]
+    fn mbc1_bank_0_selects_the_next_bank_up() {
+        let mut mbc = Mbc1::new(numbered_rom(128), Vec::new());
+        for (bank2, bank) in [(0, 0x01), (1, 0x21), (2, 0x41), (3, 0x61)] {
+            mbc.write_rom(0x4000, bank2);
+            mbc.write_rom(0x2000, 0x00);
+            assert_eq!(bank_at(&mbc, 0x4000), bank);
+            // Only the low 5 bits are checked for zero
+            mbc.write_rom(0x2000, 0x20);
+            assert_eq!(bank_at(&mbc, 0x4000), bank);
+            mbc.write_rom(0x2000, 0x05);
+            assert_eq!(bank_at(&mbc, 0x4000), bank + 4);
+        }
+    }
+
+    #[test]
+    fn mbc1_mode_1_applies_bank2_to_the_low_area_and_ram() {
+        let mut mbc = Mbc1::new(numbered_rom(128), vec![0; 0x8000]);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x02);
+        assert_eq!(bank_at(&mbc, 0x0000), 0x00);
+        mbc.write_ram(0xA000, 0x11);
+
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(bank_at(&mbc, 0x0000), 0x40);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x41);
+        assert_eq!(mbc.read_ram(0xA000), 0x00);
+        mbc.write_ram(0xA000, 0x22);
+        mbc.write_rom(0x4000, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0x11);
+
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_rom(0x6000, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0x11);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_ram(0xA000), 0x22);
+    }
+
+    #[test]
+    fn mbc1m_takes_four_bits_from_bank1() {
+        let mut rom = numbered_rom(64);
+        for game in [0x00, 0x10, 0x20, 0x30] {
+            rom[game * 0x4000 + 0x0104..game * 0x4000 + 0x0134].fill(0xCE);
+        }
+        let mut mbc = Mbc1::new(rom, Vec::new());
+        assert!(mbc.multicart);
+        mbc.write_rom(0x4000, 0x01);
+        mbc.write_rom(0x2000, 0x02);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x12);
+        mbc.write_rom(0x2000, 0x12);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x12);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(bank_at(&mbc, 0x0000), 0x10);
+
+        // A 1MB MBC1 game has its logo in bank 0 only
+        let mut rom = numbered_rom(64);
+        rom[0x0104..0x0134].fill(0xCE);
+        assert!(!Mbc1::new(rom, Vec::new()).multicart);
+    }
+
+    #[test]
+    fn mbc2_address_bit_8_picks_ram_enable_or_rom_bank() {
+        let mut mbc = Mbc2::new(numbered_rom(16));
+        mbc.write_rom(0x2100, 0x0A);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x0A);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+
+        mbc.write_rom(0x2000, 0x0A);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x0A);
+        assert_eq!(mbc.read_ram(0xA000), 0xF0);
+
+        mbc.write_rom(0x0100, 0x00);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x01);
+        mbc.write_rom(0x3EFF, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn mbc2_ram_is_512_nibbles_echoed_across_the_area() {
+        let mut mbc = Mbc2::new(numbered_rom(16));
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(0xA000, 0xAB);
+        for addr in [0xA000, 0xA200, 0xB000, 0xBE00] {
+            assert_eq!(mbc.read_ram(addr), 0xFB);
+        }
+        mbc.write_ram(0xBFFF, 0x05);
+        assert_eq!(mbc.read_ram(0xA1FF), 0xF5);
+        assert_eq!(mbc.save_data()[0x1FF], 0x05);
+    }
+
+    #[test]
+    fn mbc5_rom_bank_is_nine_bits_and_can_be_0() {
+        let mut mbc = Mbc5::new(numbered_rom(512), Vec::new(), false);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x001);
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x000);
+        mbc.write_rom(0x2000, 0x34);
+        mbc.write_rom(0x3000, 0x01);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x134);
+        mbc.write_rom(0x2000, 0xFF);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x1FF);
+        mbc.write_rom(0x3000, 0x00);
+        assert_eq!(bank_at(&mbc, 0x4000), 0x0FF);
+        assert_eq!(bank_at(&mbc, 0x0000), 0x000);
+    }
+
+    #[test]
+    fn mbc5_rumble_bit_is_not_part_of_the_ram_bank() {
+        let mut mbc = Mbc5::new(numbered_rom(4), vec![0; 0x20000], true);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_ram(0xA000, 0x33);
+        mbc.write_rom(0x4000, 0x0B);
+        assert!(mbc.rumble());
+        assert_eq!(mbc.read_ram(0xA000), 0x33);
+        mbc.write_rom(0x4000, 0x03);
+        assert!(!mbc.rumble());
+
+        // Without a motor the same bit selects banks 8-15
+        let mut mbc = Mbc5::new(numbered_rom(4), vec![0; 0x20000], false);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_ram(0xA000, 0x33);
+        mbc.write_rom(0x4000, 0x0B);
+        assert!(!mbc.rumble());
+        assert_eq!(mbc.read_ram(0xA000), 0x00);
+    }
+}
@@ -0,0 +1,246 @@
+// Game Boy Color hardware — colour palette RAM and VRAM DMA (HDMA).
+//
+// CGB mode is on when a CGB-capable cartridge runs on CGB hardware. On top of
+// the DMG memory map it adds:
+//   0xFF4D        KEY1: prepare a CPU speed switch (taken by STOP)
+//   0xFF4F        VBK: VRAM bank (bank 1 holds extra tiles and the BG attribute maps)
+//   0xFF51–0xFF55 HDMA1–HDMA5: VRAM DMA source, destination, length and mode
+//   0xFF68–0xFF6B BCPS/BCPD, OCPS/OCPD: background and object palette RAM
+//   0xFF70        SVBK: WRAM bank mapped at 0xD000–0xDFFF (1–7)
+//
+// The banks themselves live in `Memory`; this module holds the palette RAM and
+// DMA state.
+
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// Bytes per 16-byte HDMA block
+pub const HDMA_BLOCK: u16 = 0x10;
+// T-cycles the CPU is paused per HDMA block at normal speed (twice that at double speed)
+pub const HDMA_BLOCK_CYCLES: u32 = 32;
+
+// ---------------------------------------------------------------------------
+// Palette RAM
+// ---------------------------------------------------------------------------
+
+/// Eight palettes of four 15-bit colours, reached through an index register
+/// (BCPS/OCPS) and a data register (BCPD/OCPD).
+#[derive(Debug, Clone)]
+pub struct PaletteRam {
+    data: [u8; 64],
+    // Byte index in bits 0-5, auto-increment in bit 7
+    index: u8,
+}
+
+impl Default for PaletteRam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaletteRam {
+    pub fn new() -> Self {
+        PaletteRam {
+            data: [0; 64],
+            index: 0,
+        }
+    }
+
+    /// Every colour white, as the CGB boot ROM leaves the background palettes.
+    pub fn fill_white(&mut self) {
+        for pair in self.data.chunks_exact_mut(2) {
+            pair.copy_from_slice(&0x7FFFu16.to_le_bytes());
+        }
+    }
+
+    /// BCPS/OCPS as read: bit 6 is unused and reads 1.
+    pub fn read_index(&self) -> u8 {
+        self.index | 0x40
+    }
+
+    pub fn write_index(&mut self, value: u8) {
+        self.index = value & 0xBF;
+    }
+
+    pub fn read_data(&self) -> u8 {
+        self.data[(self.index & 0x3F) as usize]
+    }
+
+    /// Write the byte at the index, then step the index if auto-increment is on.
+    pub fn write_data(&mut self, value: u8) {
+        self.data[(self.index & 0x3F) as usize] = value;
+        if self.index & 0x80 != 0 {
+            self.index = 0x80 | (self.index.wrapping_add(1) & 0x3F);
+        }
+    }
+
+    /// Colour `color_id` of palette `palette` as 15-bit BGR555.
+    pub fn color(&self, palette: u8, color_id: u8) -> u16 {
+        let offset = ((palette & 0x07) as usize * 4 + (color_id & 0x03) as usize) * 2;
+        u16::from_le_bytes([self.data[offset], self.data[offset + 1]])
+    }
+
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.bytes(&self.data);
+        w.u8(self.index);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        r.bytes_into(&mut self.data)?;
+        self.index = r.u8()? & 0xBF;
+        Ok(())
+    }
+}
+
+/// Widen a BGR555 colour to 8 bits per channel, as (r, g, b).
+pub fn rgb888(color: u16) -> (u8, u8, u8) {
+    let widen = |c: u16| {
+        let c = (c & 0x1F) as u8;
+        (c << 3) | (c >> 2)
+    };
+    (widen(color), widen(color >> 5), widen(color >> 10))
+}
+
+// ---------------------------------------------------------------------------
+// VRAM DMA
+// ---------------------------------------------------------------------------
+
+/// What a write to HDMA5 asks `Memory` to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HdmaStart {
+    /// Copy every block now (general-purpose DMA).
+    General,
+    /// Copy one block at the start of each HBlank.
+    HBlank,
+    /// An active HBlank transfer was stopped.
+    Cancelled,
+}
+
+/// HDMA1–HDMA5: a copy of 16-byte blocks from ROM, cartridge RAM or WRAM
+/// into the current VRAM bank.
+#[derive(Debug, Clone, Default)]
+pub struct Hdma {
+    source: u16,
+    dest: u16,
+    // Blocks still to copy, 0-128
+    blocks_left: u8,
+    // An HBlank transfer is in progress
+    hblank_active: bool,
+}
+
+impl Hdma {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// HDMA5 as read: bit 7 clear while an HBlank transfer is active, and the
+    /// remaining length in blocks minus one (0xFF once a transfer has finished).
+    pub fn read_control(&self) -> u8 {
+        let length = self.blocks_left.wrapping_sub(1) & 0x7F;
+        if self.hblank_active {
+            length
+        } else {
+            0x80 | length
+        }
+    }
+
+    /// Write one of HDMA1–HDMA5. Returns what a write to HDMA5 starts.
+    pub fn write(&mut self, addr: u16, value: u8) -> Option<HdmaStart> {
+        match addr {
+            0xFF51 => self.source = (self.source & 0x00FF) | ((value as u16) << 8),
+            0xFF52 => self.source = (self.source & 0xFF00) | (value & 0xF0) as u16,
+            0xFF53 => self.dest = (self.dest & 0x00FF) | (((value & 0x1F) as u16) << 8),
+            0xFF54 => self.dest = (self.dest & 0xFF00) | (value & 0xF0) as u16,
+            0xFF55 => {
+                if self.hblank_active && value & 0x80 == 0 {
+                    self.hblank_active = false;
+                    return Some(HdmaStart::Cancelled);
+                }
+                self.blocks_left = (value & 0x7F) + 1;
+                if value & 0x80 != 0 {
+                    self.hblank_active = true;
+                    return Some(HdmaStart::HBlank);
+                }
+                return Some(HdmaStart::General);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Whether an HBlank transfer has blocks left to copy.
+    pub fn hblank_active(&self) -> bool {
+        self.hblank_active
+    }
+
+    /// Source and VRAM destination of the next block, advancing past it.
+    /// Returns None once the transfer is done.
+    pub fn next_block(&mut self) -> Option<(u16, u16)> {
+        if self.blocks_left == 0 {
+            self.hblank_active = false;
+            return None;
+        }
+        let block = (self.source, 0x8000 | (self.dest & 0x1FF0));
+        self.source = self.source.wrapping_add(HDMA_BLOCK);
+        self.dest = self.dest.wrapping_add(HDMA_BLOCK) & 0x1FF0;
+        self.blocks_left -= 1;
+        if self.blocks_left == 0 {
+            self.hblank_active = false;
+        }
+        Some(block)
+    }
+
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.u16(self.source);
+        w.u16(self.dest);
+        w.u8(self.blocks_left);
+        w.bool(self.hblank_active);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.source = r.u16()?;
+        self.dest = r.u16()? & 0x1FF0;
+        self.blocks_left = r.u8()?;
+        if self.blocks_left > 128 {
+            return Err(StateError::Invalid("HDMA length"));
+        }
+        self.hblank_active = r.bool()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_index_reads_bit_6_set() {
+        let mut palettes = PaletteRam::new();
+        palettes.write_index(0x05);
+        assert_eq!(palettes.read_index(), 0x45);
+        palettes.write_index(0xFF);
+        assert_eq!(palettes.read_index(), 0xFF);
+    }
+
+    #[test]
+    fn auto_increment_wraps_past_the_last_byte() {
+        let mut palettes = PaletteRam::new();
+        palettes.write_index(0xBE);
+        palettes.write_data(0x11);
+        palettes.write_data(0x22);
+        assert_eq!(palettes.read_index(), 0xC0);
+        palettes.write_data(0x33);
+        assert_eq!(palettes.read_index(), 0xC1);
+        assert_eq!(palettes.color(7, 3), 0x2211);
+        assert_eq!(palettes.color(0, 0), 0x0033);
+    }
+
+    #[test]
+    fn data_writes_without_auto_increment_keep_the_index() {
+        let mut palettes = PaletteRam::new();
+        palettes.write_index(0x3F);
+        palettes.write_data(0x12);
+        palettes.write_data(0x34);
+        assert_eq!(palettes.read_index(), 0x7F);
+        assert_eq!(palettes.read_data(), 0x34);
+    }
+}
@@ -0,0 +1,1179 @@
+// Debugger — breakpoints, watchpoints and stepping on top of `GameBoy`.
+//
+// The debugger runs the machine an instruction at a time and checks, before
+// each one:
+//   - breakpoints on PC, optionally only while a register condition holds
+//   - execute watchpoints on address ranges
+//   - conditions on register values, which break when they become true
+//   - unimplemented opcodes (on by default)
+// and after each one:
+//   - read and write watchpoints, which the bus records as the CPU accesses memory
+//   - interrupt dispatch (off by default), stopping at the vector
+//   - the end of a step-in, step-over or step-out
+//
+// Frontends call `run_frame` in place of `GameBoy::run_frame`. It returns the
+// `Break` that stopped the machine, if any, after which the debugger stays
+// paused until resumed or stepped. `command` parses the console commands
+// shared by the native terminal and the web IDE.
+//
+// Breakpoints, watchpoints and conditions share one numbering, like gdb's.
+// Resuming from a break found before an instruction runs that instruction,
+// rather than stopping on it again.
+
+use crate::cpu::{self, Registers};
+use crate::disasm;
+use crate::gameboy::{GameBoy, Step, CYCLES_PER_FRAME};
+use std::fmt;
+
+// ---------------------------------------------------------------------------
+// Watchpoints
+// ---------------------------------------------------------------------------
+
+/// Kind of memory access a watchpoint can catch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+/// A range of addresses, inclusive, watched for some kinds of access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Watchpoint {
+    /// Watch `start..=end` for the accesses named in `kinds`, any of "r", "w"
+    /// and "x" run together (e.g. "rw").
+    pub fn new(start: u16, end: u16, kinds: &str) -> Result<Watchpoint, DebuggerError> {
+        if end < start || kinds.is_empty() || kinds.chars().any(|c| !"rwx".contains(c)) {
+            return Err(DebuggerError::InvalidArgument(format!(
+                "watch {:04X}-{:04X} {}",
+                start, end, kinds
+            )));
+        }
+        Ok(Watchpoint {
+            start,
+            end,
+            read: kinds.contains('r'),
+            write: kinds.contains('w'),
+            execute: kinds.contains('x'),
+        })
+    }
+
+    /// Whether an `access` to `addr` sets this watchpoint off.
+    pub fn hits(&self, addr: u16, access: Access) -> bool {
+        let watched = match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+            Access::Execute => self.execute,
+        };
+        watched && (self.start..=self.end).contains(&addr)
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "0x{:04X}", self.start)?;
+        } else {
+            write!(f, "0x{:04X}-0x{:04X}", self.start, self.end)?;
+        }
+        write!(f, " ")?;
+        for (on, c) in [(self.read, 'r'), (self.write, 'w'), (self.execute, 'x')] {
+            if on {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An access that hit a watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    /// Address of the instruction that made the access.
+    pub pc: u16,
+    pub addr: u16,
+    pub access: Access,
+    /// The byte read or written; the opcode for an execute hit.
+    pub value: u8,
+}
+
+impl fmt::Display for WatchHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.access {
+            Access::Read => write!(
+                f,
+                "PC=0x{:04X}: read 0x{:02X} from 0x{:04X}",
+                self.pc, self.value, self.addr
+            ),
+            Access::Write => write!(
+                f,
+                "PC=0x{:04X}: write 0x{:02X} to 0x{:04X}",
+                self.pc, self.value, self.addr
+            ),
+            Access::Execute => write!(f, "PC=0x{:04X}: execute", self.pc),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Conditions
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    F,
+    B,
+    C,
+    D,
+    E,
+    H,
+    L,
+    AF,
+    BC,
+    DE,
+    HL,
+    SP,
+    PC,
+}
+
+impl Register {
+    const ALL: [Register; 14] = [
+        Register::A,
+        Register::F,
+        Register::B,
+        Register::C,
+        Register::D,
+        Register::E,
+        Register::H,
+        Register::L,
+        Register::AF,
+        Register::BC,
+        Register::DE,
+        Register::HL,
+        Register::SP,
+        Register::PC,
+    ];
+
+    /// Register by its lowercase name, e.g. "a" or "hl".
+    pub fn from_name(name: &str) -> Option<Register> {
+        Register::ALL.into_iter().find(|r| r.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Register::A => "a",
+            Register::F => "f",
+            Register::B => "b",
+            Register::C => "c",
+            Register::D => "d",
+            Register::E => "e",
+            Register::H => "h",
+            Register::L => "l",
+            Register::AF => "af",
+            Register::BC => "bc",
+            Register::DE => "de",
+            Register::HL => "hl",
+            Register::SP => "sp",
+            Register::PC => "pc",
+        }
+    }
+
+    pub fn read(self, r: &Registers) -> u16 {
+        let pair = |hi: u8, lo: u8| ((hi as u16) << 8) | lo as u16;
+        match self {
+            Register::A => r.a as u16,
+            Register::F => r.f as u16,
+            Register::B => r.b as u16,
+            Register::C => r.c as u16,
+            Register::D => r.d as u16,
+            Register::E => r.e as u16,
+            Register::H => r.h as u16,
+            Register::L => r.l as u16,
+            Register::AF => pair(r.a, r.f),
+            Register::BC => pair(r.b, r.c),
+            Register::DE => pair(r.d, r.e),
+            Register::HL => pair(r.h, r.l),
+            Register::SP => r.stack_pointer,
+            Register::PC => r.program_counter,
+        }
+    }
+
+    /// Set the register; 8-bit registers take the low byte of `value`. The
+    /// low nibble of F always reads 0, so it is dropped.
+    pub fn write(self, r: &mut Registers, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        match self {
+            Register::A => r.a = lo,
+            Register::F => r.f = lo & 0xF0,
+            Register::B => r.b = lo,
+            Register::C => r.c = lo,
+            Register::D => r.d = lo,
+            Register::E => r.e = lo,
+            Register::H => r.h = lo,
+            Register::L => r.l = lo,
+            Register::AF => (r.a, r.f) = (hi, lo & 0xF0),
+            Register::BC => (r.b, r.c) = (hi, lo),
+            Register::DE => (r.d, r.e) = (hi, lo),
+            Register::HL => (r.h, r.l) = (hi, lo),
+            Register::SP => r.stack_pointer = value,
+            Register::PC => r.program_counter = value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    // "<=" and ">=" ahead of "<" and ">", so parsing finds the longest match
+    const ALL: [Comparison; 6] = [
+        Comparison::Eq,
+        Comparison::Ne,
+        Comparison::Le,
+        Comparison::Ge,
+        Comparison::Lt,
+        Comparison::Gt,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+
+    fn compare(self, left: u16, right: u16) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Ne => left != right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+/// A register compared with a value, e.g. `a == 0x42` or `hl >= 0xC000`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub register: Register,
+    pub comparison: Comparison,
+    pub value: u16,
+}
+
+impl Condition {
+    /// Parse `<register> <comparison> <value>`; the spaces are optional.
+    pub fn parse(text: &str) -> Result<Condition, DebuggerError> {
+        let invalid = || DebuggerError::InvalidArgument(format!("condition '{}'", text));
+        let text = text.trim();
+        let (at, comparison) = text
+            .char_indices()
+            .find_map(|(i, _)| {
+                Comparison::ALL
+                    .into_iter()
+                    .find(|c| text[i..].starts_with(c.symbol()))
+                    .map(|c| (i, c))
+            })
+            .ok_or_else(invalid)?;
+        let register =
+            Register::from_name(&text[..at].trim().to_ascii_lowercase()).ok_or_else(invalid)?;
+        let value =
+            parse_number(text[at + comparison.symbol().len()..].trim()).ok_or_else(invalid)?;
+        Ok(Condition {
+            register,
+            comparison,
+            value,
+        })
+    }
+
+    pub fn holds(&self, registers: &Registers) -> bool {
+        self.comparison
+            .compare(self.register.read(registers), self.value)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = if self.register.name().len() == 1 {
+            2
+        } else {
+            4
+        };
+        write!(
+            f,
+            "{} {} 0x{:0digits$X}",
+            self.register.name(),
+            self.comparison.symbol(),
+            self.value,
+            digits = digits
+        )
+    }
+}
+
+/// A number in hex with a `0x` or `$` prefix, or in decimal.
+fn parse_number(text: &str) -> Option<u16> {
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix('$')) {
+        u16::from_str_radix(hex, 16).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Breaks
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebuggerError {
+    /// The console has no such command.
+    UnknownCommand(String),
+    /// A command argument could not be understood.
+    InvalidArgument(String),
+    /// No breakpoint, watchpoint or condition has this number.
+    NoSuchBreak(u32),
+}
+
+impl fmt::Display for DebuggerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebuggerError::UnknownCommand(name) => {
+                write!(f, "unknown command '{}' (try 'help')", name)
+            }
+            DebuggerError::InvalidArgument(what) => write!(f, "invalid {}", what),
+            DebuggerError::NoSuchBreak(id) => write!(f, "no breakpoint number {}", id),
+        }
+    }
+}
+
+impl std::error::Error for DebuggerError {}
+
+/// Why the debugger stopped the machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Break {
+    /// A step-in, step-over or step-out finished.
+    Step {
+        pc: u16,
+    },
+    /// Stopped on request.
+    Pause {
+        pc: u16,
+    },
+    Breakpoint {
+        id: u32,
+        pc: u16,
+    },
+    Watchpoint {
+        id: u32,
+        hit: WatchHit,
+    },
+    /// A condition became true before the instruction at `pc`.
+    Condition {
+        id: u32,
+        pc: u16,
+        condition: Condition,
+    },
+    /// An interrupt was dispatched; PC is at its vector.
+    Interrupt {
+        vector: u16,
+    },
+    /// The instruction at `pc` has no LR35902 opcode.
+    UnimplementedOpcode {
+        pc: u16,
+        opcode: u8,
+    },
+}
+
+impl fmt::Display for Break {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Break::Step { pc } => write!(f, "stepped to 0x{:04X}", pc),
+            Break::Pause { pc } => write!(f, "paused at 0x{:04X}", pc),
+            Break::Breakpoint { id, pc } => write!(f, "breakpoint {} at 0x{:04X}", id, pc),
+            Break::Watchpoint { id, hit } => write!(f, "watchpoint {}: {}", id, hit),
+            Break::Condition { id, pc, condition } => {
+                write!(f, "condition {} ({}) at 0x{:04X}", id, condition, pc)
+            }
+            Break::Interrupt { vector } => {
+                write!(
+                    f,
+                    "{} interrupt at 0x{:04X}",
+                    interrupt_name(*vector),
+                    vector
+                )
+            }
+            Break::UnimplementedOpcode { pc, opcode } => {
+                write!(f, "unimplemented opcode 0x{:02X} at 0x{:04X}", opcode, pc)
+            }
+        }
+    }
+}
+
+fn interrupt_name(vector: u16) -> &'static str {
+    match vector {
+        0x0040 => "VBlank",
+        0x0048 => "LCD STAT",
+        0x0050 => "Timer",
+        0x0058 => "Serial",
+        _ => "Joypad",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepMode {
+    /// Run one instruction, following calls and interrupts.
+    In,
+    /// Run one instruction, running a CALL or RST through to its return.
+    Over,
+    /// Run until the current function returns.
+    Out,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stepping {
+    In,
+    // Until PC is back at the return address with the stack as it was
+    Over { return_pc: u16, sp: u16 },
+    // Until a return pops the stack above `sp`
+    Out { sp: u16 },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Breakpoint {
+        addr: u16,
+        condition: Option<Condition>,
+    },
+    Watchpoint(Watchpoint),
+    Condition {
+        condition: Condition,
+        // Held before the last instruction, so only a change breaks
+        held: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    id: u32,
+    kind: Kind,
+}
+
+// ---------------------------------------------------------------------------
+// Debugger
+// ---------------------------------------------------------------------------
+
+pub struct Debugger {
+    entries: Vec<Entry>,
+    next_id: u32,
+    break_on_interrupt: bool,
+    break_on_unimplemented: bool,
+    stepping: Option<Stepping>,
+    paused: bool,
+    // The next instruction runs without being checked: it is what we stopped on
+    resuming: bool,
+    // Read/write watchpoints changed since they were handed to the bus
+    watch_dirty: bool,
+    // The last run_frame ended with the PPU finishing a frame
+    frame_completed: bool,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debugger {
+    /// A debugger with nothing set, breaking on unimplemented opcodes only.
+    pub fn new() -> Self {
+        Debugger {
+            entries: Vec::new(),
+            next_id: 1,
+            break_on_interrupt: false,
+            break_on_unimplemented: true,
+            stepping: None,
+            paused: false,
+            resuming: false,
+            watch_dirty: false,
+            frame_completed: false,
+        }
+    }
+
+    /// Break when PC reaches `addr`, and `condition` holds if given. Returns
+    /// the breakpoint's number.
+    pub fn add_breakpoint(&mut self, addr: u16, condition: Option<Condition>) -> u32 {
+        self.add(Kind::Breakpoint { addr, condition })
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> u32 {
+        self.watch_dirty = true;
+        self.add(Kind::Watchpoint(watchpoint))
+    }
+
+    /// Break whenever `condition` goes from false to true.
+    pub fn add_condition(&mut self, condition: Condition) -> u32 {
+        self.add(Kind::Condition {
+            condition,
+            held: false,
+        })
+    }
+
+    fn add(&mut self, kind: Kind) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(Entry { id, kind });
+        id
+    }
+
+    /// Remove the breakpoint, watchpoint or condition numbered `id`.
+    pub fn remove(&mut self, id: u32) -> Result<(), DebuggerError> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or(DebuggerError::NoSuchBreak(id))?;
+        self.entries.remove(index);
+        self.watch_dirty = true;
+        Ok(())
+    }
+
+    /// Remove every breakpoint, watchpoint and condition.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.watch_dirty = true;
+    }
+
+    pub fn set_break_on_interrupt(&mut self, enabled: bool) {
+        self.break_on_interrupt = enabled;
+    }
+
+    pub fn set_break_on_unimplemented(&mut self, enabled: bool) {
+        self.break_on_unimplemented = enabled;
+    }
+
+    /// Whether the machine is stopped, waiting to be resumed or stepped.
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop before the next instruction.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.stepping = None;
+    }
+
+    /// Carry on running until something breaks.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.stepping = None;
+    }
+
+    /// Whether the last `run_frame` ran until the PPU finished a frame, like
+    /// the return value of `GameBoy::run_frame`.
+    pub fn frame_completed(&self) -> bool {
+        self.frame_completed
+    }
+
+    /// Start a step from the current instruction; `run_frame` returns
+    /// `Break::Step` when it is done, unless something else breaks first.
+    pub fn step(&mut self, gb: &GameBoy, mode: StepMode) {
+        let r = gb.cpu().registers();
+        let opcode = gb.memory().read_byte(r.program_counter);
+        let call = matches!(opcode, 0xC4 | 0xCC | 0xCD | 0xD4 | 0xDC) || opcode & 0xC7 == 0xC7;
+        self.stepping = Some(match mode {
+            StepMode::Over if call => Stepping::Over {
+                return_pc: r
+                    .program_counter
+                    .wrapping_add(cpu::instruction_length(opcode)),
+                sp: r.stack_pointer,
+            },
+            StepMode::In | StepMode::Over => Stepping::In,
+            StepMode::Out => Stepping::Out {
+                sp: r.stack_pointer,
+            },
+        });
+        self.paused = false;
+    }
+
+    /// Run until the PPU finishes a frame (see `GameBoy::run_frame`) or
+    /// something breaks. Does nothing while paused.
+    pub fn run_frame(&mut self, gb: &mut GameBoy) -> Option<Break> {
+        self.run(gb, |gb| gb.step_instruction())
+    }
+
+    /// `run_frame`, passing a log line for each instruction to `log`.
+    pub fn run_frame_logged(
+        &mut self,
+        gb: &mut GameBoy,
+        mut log: impl FnMut(String),
+    ) -> Option<Break> {
+        self.run(gb, |gb| {
+            let (step, line) = gb.step_instruction_logged();
+            log(line);
+            step
+        })
+    }
+
+    fn run(
+        &mut self,
+        gb: &mut GameBoy,
+        mut step_instruction: impl FnMut(&mut GameBoy) -> Step,
+    ) -> Option<Break> {
+        self.frame_completed = false;
+        if self.paused {
+            return None;
+        }
+        if std::mem::take(&mut self.watch_dirty) {
+            let watchpoints = self.watchpoints().filter(|w| w.read || w.write).collect();
+            gb.bus_mut().set_watchpoints(watchpoints);
+        }
+        let mut cycles = 0;
+        while cycles < CYCLES_PER_FRAME {
+            let cpu = gb.cpu();
+            let idle = cpu.halted || cpu.stopped;
+            let pc = cpu.registers().program_counter;
+            let opcode = gb.memory().read_byte(pc);
+            let resuming = std::mem::take(&mut self.resuming);
+            if let Some(stop) = self.before_step(gb, opcode, idle || resuming) {
+                self.resuming = true;
+                return Some(self.stop(gb, stop));
+            }
+            let step = step_instruction(gb);
+            cycles += step.cycles;
+            if let Some(mut stop) = self.after_step(gb, &step, opcode, idle) {
+                // A step that lands on a breakpoint reports the breakpoint
+                if let Break::Step { pc } = stop {
+                    let opcode = gb.memory().read_byte(pc);
+                    if let Some(landed) = self.before_step(gb, opcode, false) {
+                        stop = landed;
+                        self.resuming = true;
+                    }
+                }
+                return Some(self.stop(gb, stop));
+            }
+            if step.frame_completed {
+                self.frame_completed = true;
+                break;
+            }
+        }
+        None
+    }
+
+    fn stop(&mut self, gb: &mut GameBoy, reason: Break) -> Break {
+        self.pause();
+        gb.bus_mut().take_watch_hits();
+        reason
+    }
+
+    /// Checks on the instruction about to run. Conditions are tracked even
+    /// when `skip` says not to break, so a change is never reported late.
+    fn before_step(&mut self, gb: &GameBoy, opcode: u8, skip: bool) -> Option<Break> {
+        let r = gb.cpu().registers();
+        let pc = r.program_counter;
+        let mut stop = None;
+        for entry in self.entries.iter_mut() {
+            let hit = match &mut entry.kind {
+                Kind::Breakpoint { addr, condition } => {
+                    *addr == pc && condition.is_none_or(|c| c.holds(r))
+                }
+                Kind::Watchpoint(watchpoint) => watchpoint.hits(pc, Access::Execute),
+                Kind::Condition { condition, held } => {
+                    let was_held = std::mem::replace(held, condition.holds(r));
+                    *held && !was_held
+                }
+            };
+            if hit && !skip && stop.is_none() {
+                stop = Some(match entry.kind {
+                    Kind::Breakpoint { .. } => Break::Breakpoint { id: entry.id, pc },
+                    Kind::Watchpoint(_) => Break::Watchpoint {
+                        id: entry.id,
+                        hit: WatchHit {
+                            pc,
+                            addr: pc,
+                            access: Access::Execute,
+                            value: opcode,
+                        },
+                    },
+                    Kind::Condition { condition, .. } => Break::Condition {
+                        id: entry.id,
+                        pc,
+                        condition,
+                    },
+                });
+            }
+        }
+        if stop.is_none()
+            && !skip
+            && self.break_on_unimplemented
+            && cpu::unimplemented_opcode(opcode)
+        {
+            stop = Some(Break::UnimplementedOpcode { pc, opcode });
+        }
+        stop
+    }
+
+    /// Checks on what the step just did. `opcode` was at PC before it, and
+    /// `idle` says the CPU was halted or stopped.
+    fn after_step(
+        &mut self,
+        gb: &mut GameBoy,
+        step: &Step,
+        opcode: u8,
+        idle: bool,
+    ) -> Option<Break> {
+        for hit in gb.bus_mut().take_watch_hits() {
+            let watchpoint = self.entries.iter().find(
+                |entry| matches!(entry.kind, Kind::Watchpoint(w) if w.hits(hit.addr, hit.access)),
+            );
+            if let Some(entry) = watchpoint {
+                return Some(Break::Watchpoint { id: entry.id, hit });
+            }
+        }
+        let r = gb.cpu().registers();
+        let pc = r.program_counter;
+        if step.interrupt && self.break_on_interrupt {
+            return Some(Break::Interrupt { vector: pc });
+        }
+        // Not an interrupt, and not a halted CPU idling through another M-cycle
+        let executed = !step.interrupt && !(idle && (gb.cpu().halted || gb.cpu().stopped));
+        let done = match self.stepping? {
+            Stepping::In => step.interrupt || executed,
+            Stepping::Over { return_pc, sp } => pc == return_pc && r.stack_pointer == sp,
+            Stepping::Out { sp } => {
+                let ret = matches!(opcode, 0xC0 | 0xC8 | 0xC9 | 0xD0 | 0xD8 | 0xD9);
+                ret && executed && r.stack_pointer > sp
+            }
+        };
+        done.then_some(Break::Step { pc })
+    }
+
+    fn watchpoints(&self) -> impl Iterator<Item = Watchpoint> + '_ {
+        self.entries.iter().filter_map(|entry| match entry.kind {
+            Kind::Watchpoint(watchpoint) => Some(watchpoint),
+            _ => None,
+        })
+    }
+
+    // -----------------------------------------------------------------------
+    // Console
+    // -----------------------------------------------------------------------
+
+    /// Run one console command, returning the text to show. Commands that
+    /// resume or step take effect on the next `run_frame`.
+    pub fn command(&mut self, gb: &mut GameBoy, line: &str) -> Result<String, DebuggerError> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(String::new());
+        };
+        let args: Vec<&str> = words.collect();
+        let arg = |i: usize, what: &str| {
+            args.get(i).copied().ok_or_else(|| {
+                DebuggerError::InvalidArgument(format!("{}: missing {}", name, what))
+            })
+        };
+        let number = |text: &str| {
+            parse_number(text)
+                .ok_or_else(|| DebuggerError::InvalidArgument(format!("number '{}'", text)))
+        };
+        let output = match name {
+            "break" | "b" => {
+                let addr = number(arg(0, "address")?)?;
+                let condition = match args.get(1) {
+                    Some(&"if") => Some(Condition::parse(&args[2..].join(" "))?),
+                    Some(_) => return Err(DebuggerError::InvalidArgument(line.to_string())),
+                    None => None,
+                };
+                let id = self.add_breakpoint(addr, condition);
+                format!("breakpoint {} at 0x{:04X}", id, addr)
+            }
+            "watch" | "w" => {
+                let range = arg(0, "address")?;
+                let (start, end) = match range.split_once('-') {
+                    Some((start, end)) => (number(start)?, number(end)?),
+                    None => (number(range)?, number(range)?),
+                };
+                let watchpoint = Watchpoint::new(start, end, args.get(1).unwrap_or(&"w"))?;
+                let id = self.add_watchpoint(watchpoint);
+                format!("watchpoint {}: {}", id, watchpoint)
+            }
+            "cond" => {
+                let condition = Condition::parse(&args.join(" "))?;
+                let id = self.add_condition(condition);
+                format!("condition {}: {}", id, condition)
+            }
+            "delete" | "d" => match args.first() {
+                Some(id) => {
+                    let id = id
+                        .parse()
+                        .map_err(|_| DebuggerError::InvalidArgument(format!("number '{}'", id)))?;
+                    self.remove(id)?;
+                    format!("deleted {}", id)
+                }
+                None => {
+                    self.clear();
+                    "deleted all".to_string()
+                }
+            },
+            "list" | "l" => self.list(),
+            "catch" => {
+                let enabled = match arg(1, "on or off")? {
+                    "on" => true,
+                    "off" => false,
+                    other => return Err(DebuggerError::InvalidArgument(other.to_string())),
+                };
+                let what = arg(0, "interrupt or unimplemented")?;
+                match what {
+                    "interrupt" => self.break_on_interrupt = enabled,
+                    "unimplemented" => self.break_on_unimplemented = enabled,
+                    other => return Err(DebuggerError::InvalidArgument(other.to_string())),
+                }
+                format!("break on {}: {}", what, if enabled { "on" } else { "off" })
+            }
+            "continue" | "c" => {
+                self.resume();
+                String::new()
+            }
+            "step" | "s" => {
+                self.step(gb, StepMode::In);
+                String::new()
+            }
+            "next" | "n" => {
+                self.step(gb, StepMode::Over);
+                String::new()
+            }
+            "finish" | "f" => {
+                self.step(gb, StepMode::Out);
+                String::new()
+            }
+            "pause" | "p" => {
+                self.pause();
+                Break::Pause {
+                    pc: gb.cpu().registers().program_counter,
+                }
+                .to_string()
+            }
+            "regs" | "r" => registers(gb),
+            "x" => {
+                let addr = number(arg(0, "address")?)?;
+                let count = args.get(1).map(|n| number(n)).transpose()?.unwrap_or(16);
+                dump(gb, addr, count)
+            }
+            "help" | "h" => HELP.to_string(),
+            _ => return Err(DebuggerError::UnknownCommand(name.to_string())),
+        };
+        Ok(output)
+    }
+
+    /// Every breakpoint, watchpoint and condition, one per line.
+    pub fn list(&self) -> String {
+        if self.entries.is_empty() {
+            return "no breakpoints".to_string();
+        }
+        self.entries
+            .iter()
+            .map(|entry| match entry.kind {
+                Kind::Breakpoint {
+                    addr,
+                    condition: Some(condition),
+                } => format!("{}: break 0x{:04X} if {}", entry.id, addr, condition),
+                Kind::Breakpoint { addr, .. } => format!("{}: break 0x{:04X}", entry.id, addr),
+                Kind::Watchpoint(watchpoint) => format!("{}: watch {}", entry.id, watchpoint),
+                Kind::Condition { condition, .. } => format!("{}: cond {}", entry.id, condition),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+const HELP: &str = "\
+break <addr> [if <cond>]  break at addr, optionally only when cond holds (b)
+watch <addr>[-<end>] [rwx] break on reads, writes or execution in a range (w; default w)
+cond <cond>               break when cond becomes true, e.g. 'a == 0x42', 'hl >= $C000'
+delete [<n>]              delete breakpoint n, or all of them (d)
+list                      list breakpoints, watchpoints and conditions (l)
+catch interrupt on|off    break when an interrupt is dispatched
+catch unimplemented on|off  break on an unimplemented opcode (default on)
+step / next / finish      step in, over a call, or out of the function (s / n / f)
+continue / pause          run until something breaks, or stop now (c / p)
+regs                      show registers and the next instruction (r)
+x <addr> [count]          dump memory (default 16 bytes)
+Numbers are decimal, or hex with a 0x or $ prefix.";
+
+/// Registers, flags and the instruction at PC on one line.
+pub fn registers(gb: &GameBoy) -> String {
+    let cpu = gb.cpu();
+    let r = cpu.registers();
+    let flag = |bit: u8, c: char| if r.f & (1 << bit) != 0 { c } else { '-' };
+    format!(
+        "A={:02X} F={:02X} B={:02X} C={:02X} D={:02X} E={:02X} H={:02X} L={:02X} \
+         SP={:04X} PC={:04X} [{}{}{}{}] IME={}{}\n{}",
+        r.a,
+        r.f,
+        r.b,
+        r.c,
+        r.d,
+        r.e,
+        r.h,
+        r.l,
+        r.stack_pointer,
+        r.program_counter,
+        flag(7, 'Z'),
+        flag(6, 'N'),
+        flag(5, 'H'),
+        flag(4, 'C'),
+        cpu.ime as u8,
+        if cpu.halted { " HALT" } else { "" },
+        location(gb)
+    )
+}
+
+/// The instruction at PC: address, bytes and disassembly.
+pub fn location(gb: &GameBoy) -> String {
+    let pc = gb.cpu().registers().program_counter;
+    disasm::decode_at(gb.memory(), pc).to_string()
+}
+
+/// `count` bytes from `addr`, sixteen to a line.
+fn dump(gb: &GameBoy, addr: u16, count: u16) -> String {
+    (0..count)
+        .step_by(16)
+        .map(|row| {
+            let start = addr.wrapping_add(row);
+            let bytes: Vec<String> = (0..(count - row).min(16))
+                .map(|i| format!("{:02X}", gb.memory().read_byte(start.wrapping_add(i))))
+                .collect();
+            format!("0x{:04X}: {}", start, bytes.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::boot::{Boot, Model};
+
+    /// A DMG past its boot ROM, about to jump to this program:
+    ///   0150: CALL 0160
+    ///   0153: NOP
+    ///   0154: (unimplemented opcode)
+    ///   0160: LD A,42
+    ///   0162: LD (C000),A
+    ///   0165: RET
+    /// Shared with the gdb stub tests.
+    pub(crate) fn machine() -> GameBoy {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0100..0x0103].copy_from_slice(&[0xC3, 0x50, 0x01]); // JP 0150
+        rom[0x0150..0x0155].copy_from_slice(&[0xCD, 0x60, 0x01, 0x00, 0xD3]);
+        rom[0x0160..0x0166].copy_from_slice(&[0x3E, 0x42, 0xEA, 0x00, 0xC0, 0xC9]);
+        GameBoy::new(rom, Some(Boot::Skip(Model::Dmg))).unwrap()
+    }
+
+    /// `machine`, stopped on a breakpoint at the CALL.
+    fn at_call(debugger: &mut Debugger) -> GameBoy {
+        let mut gb = machine();
+        let id = debugger.add_breakpoint(0x0150, None);
+        assert_eq!(
+            debugger.run_frame(&mut gb),
+            Some(Break::Breakpoint { id, pc: 0x0150 })
+        );
+        debugger.remove(id).unwrap();
+        gb
+    }
+
+    #[test]
+    fn breakpoint_stops_before_its_instruction_and_resume_runs_it() {
+        let mut gb = machine();
+        let mut debugger = Debugger::new();
+        let id = debugger.add_breakpoint(0x0160, None);
+        let stop = debugger.run_frame(&mut gb);
+        assert_eq!(stop, Some(Break::Breakpoint { id, pc: 0x0160 }));
+        assert!(debugger.paused());
+        assert_eq!(gb.cpu().registers().a, 0x01);
+
+        // Paused: another frame does nothing
+        assert_eq!(debugger.run_frame(&mut gb), None);
+        assert_eq!(gb.cpu().registers().program_counter, 0x0160);
+
+        // Resuming runs the instruction instead of stopping on it again
+        debugger.resume();
+        debugger.add_breakpoint(0x0153, None);
+        let stop = debugger.run_frame(&mut gb);
+        assert_eq!(stop, Some(Break::Breakpoint { id: 2, pc: 0x0153 }));
+        assert_eq!(gb.cpu().registers().a, 0x42);
+    }
+
+    #[test]
+    fn conditional_breakpoint_waits_for_its_condition() {
+        let mut gb = machine();
+        let mut debugger = Debugger::new();
+        let condition = Condition::parse("a == 0x42").unwrap();
+        debugger.add_breakpoint(0x0160, Some(Condition::parse("a == 0").unwrap()));
+        let id = debugger.add_breakpoint(0x0153, Some(condition));
+        let stop = debugger.run_frame(&mut gb);
+        assert_eq!(stop, Some(Break::Breakpoint { id, pc: 0x0153 }));
+    }
+
+    #[test]
+    fn write_watchpoint_reports_the_access() {
+        let mut gb = machine();
+        let mut debugger = Debugger::new();
+        debugger.add_watchpoint(Watchpoint::new(0xC000, 0xC0FF, "r").unwrap());
+        let id = debugger.add_watchpoint(Watchpoint::new(0xC000, 0xC000, "w").unwrap());
+        let stop = debugger.run_frame(&mut gb);
+        let hit = WatchHit {
+            pc: 0x0162,
+            addr: 0xC000,
+            access: Access::Write,
+            value: 0x42,
+        };
+        assert_eq!(stop, Some(Break::Watchpoint { id, hit }));
+        assert_eq!(gb.cpu().registers().program_counter, 0x0165);
+    }
+
+    #[test]
+    fn execute_watchpoint_stops_before_the_instruction() {
+        let mut gb = machine();
+        let mut debugger = Debugger::new();
+        let id = debugger.add_watchpoint(Watchpoint::new(0x0160, 0x0165, "x").unwrap());
+        let stop = debugger.run_frame(&mut gb);
+        let hit = WatchHit {
+            pc: 0x0160,
+            addr: 0x0160,
+            access: Access::Execute,
+            value: 0x3E,
+        };
+        assert_eq!(stop, Some(Break::Watchpoint { id, hit }));
+    }
+
+    #[test]
+    fn condition_breaks_when_it_becomes_true() {
+        let mut debugger = Debugger::new();
+        let mut gb = at_call(&mut debugger);
+        gb.cpu_mut().registers_mut().a = 0;
+        let condition = Condition::parse("a == 0x42").unwrap();
+        let id = debugger.add_condition(condition);
+        debugger.resume();
+        let stop = debugger.run_frame(&mut gb);
+        assert_eq!(
+            stop,
+            Some(Break::Condition {
+                id,
+                pc: 0x0162,
+                condition
+            })
+        );
+
+        // Still true afterwards, so it does not break again
+        debugger.resume();
+        let stop = debugger.run_frame(&mut gb);
+        assert!(matches!(stop, Some(Break::UnimplementedOpcode { .. })));
+    }
+
+    #[test]
+    fn step_in_enters_the_call() {
+        let mut debugger = Debugger::new();
+        let mut gb = at_call(&mut debugger);
+        debugger.step(&gb, StepMode::In);
+        assert_eq!(
+            debugger.run_frame(&mut gb),
+            Some(Break::Step { pc: 0x0160 })
+        );
+        debugger.step(&gb, StepMode::In);
+        assert_eq!(
+            debugger.run_frame(&mut gb),
+            Some(Break::Step { pc: 0x0162 })
+        );
+    }
+
+    #[test]
+    fn step_over_runs_the_whole_call() {
+        let mut debugger = Debugger::new();
+        let mut gb = at_call(&mut debugger);
+        debugger.step(&gb, StepMode::Over);
+        assert_eq!(
+            debugger.run_frame(&mut gb),
+            Some(Break::Step { pc: 0x0153 })
+        );
+        assert_eq!(gb.cpu().registers().a, 0x42);
+    }
+
+    #[test]
+    fn step_out_returns_to_the_caller() {
+        let mut debugger = Debugger::new();
+        let mut gb = at_call(&mut debugger);
+        debugger.step(&gb, StepMode::In);
+        debugger.run_frame(&mut gb);
+        debugger.step(&gb, StepMode::Out);
+        assert_eq!(
+            debugger.run_frame(&mut gb),
+            Some(Break::Step { pc: 0x0153 })
+        );
+    }
+
+    #[test]
+    fn break_on_interrupt_stops_at_the_vector() {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0100] = 0xFB; // EI, then NOPs
+        let mut gb = GameBoy::new(rom, Some(Boot::Skip(Model::Dmg))).unwrap();
+        gb.memory_mut().write_byte(0xFF40, 0x00);
+        gb.memory_mut().write_byte(0xFFFF, 0x04);
+        gb.memory_mut().write_byte(0xFF0F, 0x04);
+        let mut debugger = Debugger::new();
+        debugger.set_break_on_interrupt(true);
+        let stop = debugger.run_frame(&mut gb);
+        assert_eq!(stop, Some(Break::Interrupt { vector: 0x0050 }));
+        assert_eq!(gb.cpu().registers().program_counter, 0x0050);
+    }
+
+    #[test]
+    fn interrupts_do_not_break_by_default() {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0100] = 0xFB; // EI, then NOPs
+        let mut gb = GameBoy::new(rom, Some(Boot::Skip(Model::Dmg))).unwrap();
+        gb.memory_mut().write_byte(0xFFFF, 0x04);
+        gb.memory_mut().write_byte(0xFF0F, 0x04);
+        let mut debugger = Debugger::new();
+        assert_eq!(debugger.run_frame(&mut gb), None);
+    }
+
+    #[test]
+    fn break_on_unimplemented_opcode_stops_before_it() {
+        let mut gb = machine();
+        let mut debugger = Debugger::new();
+        let stop = debugger.run_frame(&mut gb);
+        assert_eq!(
+            stop,
+            Some(Break::UnimplementedOpcode {
+                pc: 0x0154,
+                opcode: 0xD3
+            })
+        );
+        assert_eq!(gb.cpu().registers().program_counter, 0x0154);
+
+        let mut gb = machine();
+        debugger.set_break_on_unimplemented(false);
+        debugger.resume();
+        assert_eq!(debugger.run_frame(&mut gb), None);
+    }
+}
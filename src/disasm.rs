@@ -0,0 +1,269 @@
+// Disassembler — LR35902 machine code to text, operands included.
+//
+// Decoding goes through the opcode tables in cpu.rs: the placeholders in a
+// mnemonic are filled in from the bytes after the opcode, so `LD DE,d16`
+// becomes `LD DE,$C000`, `JR NZ,r8` shows the address it jumps to and
+// `PREFIX CB` becomes the CB instruction it selects. The eleven opcodes with
+// no instruction come out as `DB $xx`.
+//
+// `decode` works on a byte slice, `decode_at` on memory as the CPU sees it,
+// and `disassemble_bank` sweeps a whole ROM bank front to back. A linear
+// sweep cannot tell code from data, so the cartridge header and any tables
+// come out as instructions too.
+
+use crate::cpu::{self, CB_INSTRUCTIONS, INSTRUCTIONS};
+use crate::memory::MemoryAccess;
+use std::fmt;
+
+/// Bytes in one ROM bank.
+pub const ROM_BANK_SIZE: usize = 0x4000;
+
+/// One instruction, decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    /// Address of the opcode.
+    pub addr: u16,
+    /// Opcode and operand bytes.
+    pub bytes: Vec<u8>,
+    /// Mnemonic with its operands, e.g. `LD DE,$C000`.
+    pub text: String,
+    /// Where a jump, call or RST goes, if that is known without running it.
+    pub target: Option<u16>,
+}
+
+impl fmt::Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        write!(
+            f,
+            "0x{:04X}: {:<9} {}",
+            self.addr,
+            bytes.join(" "),
+            self.text
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisasmError {
+    /// The ROM has only `banks` banks.
+    NoSuchBank { bank: usize, banks: usize },
+}
+
+impl fmt::Display for DisasmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisasmError::NoSuchBank { bank, banks } => {
+                write!(f, "no ROM bank {} (the ROM has {})", bank, banks)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DisasmError {}
+
+/// Decode the instruction at the start of `bytes`, which sits at `addr`.
+/// None if `bytes` is empty or ends partway through the instruction.
+pub fn decode(bytes: &[u8], addr: u16) -> Option<Decoded> {
+    let opcode = *bytes.first()?;
+    let bytes = bytes.get(..cpu::instruction_length(opcode) as usize)?;
+    Some(decode_instruction(bytes, addr))
+}
+
+/// Decode the instruction at `addr`, reading memory as the CPU would see it
+/// with the current banks mapped in.
+pub fn decode_at(memory: &dyn MemoryAccess, addr: u16) -> Decoded {
+    let length = cpu::instruction_length(memory.read_byte(addr));
+    let bytes: Vec<u8> = (0..length)
+        .map(|i| memory.read_byte(addr.wrapping_add(i)))
+        .collect();
+    decode_instruction(&bytes, addr)
+}
+
+/// Decode `bytes` front to back as if loaded at `origin`. Bytes left over at
+/// the end that do not make a whole instruction come out as `DB`.
+pub fn disassemble(bytes: &[u8], origin: u16) -> Vec<Decoded> {
+    let mut listing = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let addr = origin.wrapping_add(offset as u16);
+        let decoded = decode(&bytes[offset..], addr).unwrap_or_else(|| Decoded {
+            addr,
+            bytes: vec![bytes[offset]],
+            text: format!("DB ${:02X}", bytes[offset]),
+            target: None,
+        });
+        offset += decoded.bytes.len();
+        listing.push(decoded);
+    }
+    listing
+}
+
+/// Linear-sweep listing of ROM bank `bank`, at the addresses it is mapped
+/// to: 0x0000–0x3FFF for bank 0, 0x4000–0x7FFF for the rest.
+pub fn disassemble_bank(rom: &[u8], bank: usize) -> Result<Vec<Decoded>, DisasmError> {
+    let banks = rom.len().div_ceil(ROM_BANK_SIZE);
+    if bank >= banks {
+        return Err(DisasmError::NoSuchBank { bank, banks });
+    }
+    let start = bank * ROM_BANK_SIZE;
+    let end = rom.len().min(start + ROM_BANK_SIZE);
+    let origin = if bank == 0 { 0x0000 } else { 0x4000 };
+    Ok(disassemble(&rom[start..end], origin))
+}
+
+/// `bytes` holds the whole instruction.
+fn decode_instruction(bytes: &[u8], addr: u16) -> Decoded {
+    let opcode = bytes[0];
+    let next = addr.wrapping_add(bytes.len() as u16);
+    let n8 = bytes.get(1).copied().unwrap_or(0);
+    let n16 = u16::from_le_bytes([n8, bytes.get(2).copied().unwrap_or(0)]);
+    let mnemonic = INSTRUCTIONS[opcode as usize].mnemonic;
+    let mut target = None;
+    let text = if cpu::unimplemented_opcode(opcode) {
+        format!("DB ${:02X}", opcode)
+    } else if opcode == 0xCB {
+        CB_INSTRUCTIONS[n8 as usize].mnemonic.to_string()
+    } else if opcode == 0x10 {
+        "STOP".to_string()
+    } else if opcode & 0xC7 == 0xC7 {
+        let vector = (opcode & 0x38) as u16;
+        target = Some(vector);
+        format!("RST ${:02X}", vector)
+    } else if mnemonic.contains("SP+r8") {
+        mnemonic.replace("SP+r8", &format!("SP{:+}", n8 as i8))
+    } else if mnemonic.contains("SP,r8") {
+        mnemonic.replace("SP,r8", &format!("SP,{}", n8 as i8))
+    } else if mnemonic.contains("r8") {
+        let to = next.wrapping_add(n8 as i8 as u16);
+        target = Some(to);
+        mnemonic.replace("r8", &format!("${:04X}", to))
+    } else if mnemonic.contains("a16") {
+        if mnemonic.starts_with("JP") || mnemonic.starts_with("CALL") {
+            target = Some(n16);
+        }
+        mnemonic.replace("a16", &format!("${:04X}", n16))
+    } else if mnemonic.contains("d16") {
+        mnemonic.replace("d16", &format!("${:04X}", n16))
+    } else if mnemonic.contains("a8") {
+        mnemonic.replace("a8", &format!("${:04X}", 0xFF00 | n8 as u16))
+    } else if mnemonic.contains("d8") {
+        mnemonic.replace("d8", &format!("${:02X}", n8))
+    } else {
+        mnemonic.to_string()
+    };
+    Decoded {
+        addr,
+        bytes: bytes.to_vec(),
+        text,
+        target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassembler_fills_in_operands() {
+        let code = [
+            0x11, 0x00, 0xC0, // 0150: LD DE,C000
+            0x20, 0xFE, // 0153: JR NZ,0153
+            0xCB, 0x7C, // 0155: BIT 7,H
+            0xE0, 0x44, // 0157: LDH (FF44),A
+            0xF8, 0xFE, // 0159: LD HL,SP-2
+            0xFF, // 015B: RST 38
+            0xD3, // 015C: unused
+            0xCD, // 015D: CALL, cut short
+        ];
+        let listing = disassemble(&code, 0x0150);
+        let text: Vec<&str> = listing.iter().map(|d| d.text.as_str()).collect();
+        assert_eq!(
+            text,
+            [
+                "LD DE,$C000",
+                "JR NZ,$0153",
+                "BIT 7,H",
+                "LDH ($FF44),A",
+                "LD HL,SP-2",
+                "RST $38",
+                "DB $D3",
+                "DB $CD"
+            ]
+        );
+        assert_eq!(listing[1].target, Some(0x0153));
+        assert_eq!(listing[5].target, Some(0x0038));
+        assert_eq!(listing[0].to_string(), "0x0150: 11 00 C0  LD DE,$C000");
+        assert_eq!(decode(&code[13..], 0x015D), None);
+    }
+
+    #[test]
+    fn relative_jumps_reach_both_ends_of_their_range() {
+        let back = decode(&[0x18, 0x80], 0x0200).unwrap();
+        assert_eq!(back.text, "JR $0182");
+        assert_eq!(back.target, Some(0x0182));
+        let forward = decode(&[0x38, 0x7F], 0x0200).unwrap();
+        assert_eq!(forward.text, "JR C,$0281");
+        assert_eq!(forward.target, Some(0x0281));
+        // Targets wrap around the address space
+        let wrapped = decode(&[0x18, 0x80], 0x0000).unwrap();
+        assert_eq!(wrapped.target, Some(0xFF82));
+    }
+
+    #[test]
+    fn cb_prefix_decodes_to_the_instruction_it_selects() {
+        let code = [0xCB, 0x00, 0xCB, 0x37, 0xCB, 0x86, 0xCB, 0xFE];
+        let listing = disassemble(&code, 0x4000);
+        let text: Vec<&str> = listing.iter().map(|d| d.text.as_str()).collect();
+        assert_eq!(text, ["RLC B", "SWAP A", "RES 0,(HL)", "SET 7,(HL)"]);
+        assert!(listing
+            .iter()
+            .all(|d| d.bytes.len() == 2 && d.target.is_none()));
+        assert_eq!(listing[3].addr, 0x4006);
+        assert_eq!(listing[3].to_string(), "0x4006: CB FE     SET 7,(HL)");
+    }
+
+    #[test]
+    fn instruction_cut_off_by_the_end_of_the_bytes() {
+        assert_eq!(decode(&[], 0x0000), None);
+        assert_eq!(decode(&[0xCB], 0x0000), None);
+        assert_eq!(decode(&[0xFA, 0x00], 0x0000), None);
+        // The sweep shows the opcode as data and decodes on from the next byte
+        let listing = disassemble(&[0x00, 0xFA, 0x00], 0x0000);
+        let text: Vec<&str> = listing.iter().map(|d| d.text.as_str()).collect();
+        assert_eq!(text, ["NOP", "DB $FA", "NOP"]);
+        assert_eq!(listing[2].addr, 0x0002);
+    }
+
+    #[test]
+    fn banks_are_listed_where_they_are_mapped() {
+        // Two whole banks and a short third one
+        let mut rom = vec![0; 2 * ROM_BANK_SIZE + 0x10];
+        rom[0x0100..0x0103].copy_from_slice(&[0xC3, 0x50, 0x01]); // JP 0150
+        rom[ROM_BANK_SIZE..ROM_BANK_SIZE + 2].copy_from_slice(&[0x18, 0xFE]); // JR -2
+        rom[2 * ROM_BANK_SIZE + 0x0F] = 0xC3; // JP, cut short
+
+        let bank0 = disassemble_bank(&rom, 0).unwrap();
+        assert_eq!(bank0.len(), ROM_BANK_SIZE - 2);
+        assert_eq!(bank0[0x100].text, "JP $0150");
+        assert_eq!(bank0[0x101].addr, 0x0103);
+        assert_eq!(bank0.last().unwrap().addr, 0x3FFF);
+
+        let bank1 = disassemble_bank(&rom, 1).unwrap();
+        assert_eq!(bank1[0].addr, 0x4000);
+        assert_eq!(bank1[0].target, Some(0x4000));
+        assert_eq!(bank1.last().unwrap().addr, 0x7FFF);
+
+        let bank2 = disassemble_bank(&rom, 2).unwrap();
+        assert_eq!(bank2.len(), 0x10);
+        assert_eq!(
+            bank2.last().unwrap().to_string(),
+            "0x400F: C3        DB $C3"
+        );
+
+        assert_eq!(
+            disassemble_bank(&rom, 3),
+            Err(DisasmError::NoSuchBank { bank: 3, banks: 3 })
+        );
+    }
+}
@@ -0,0 +1,242 @@
+// OAM DMA — the 160-byte copy into OAM started by writing a page to 0xFF46.
+//
+// The transfer runs alongside the CPU, one byte per M-cycle at CPU speed:
+// one M-cycle of setup after the write, then 160 M-cycles copying from
+// 0xXX00–0xXX9F to 0xFE00–0xFE9F. While bytes are being copied the DMA owns
+// OAM and the memory bus it reads from:
+//   - CPU reads of OAM return 0xFF and writes are lost
+//   - CPU reads from the source's bus return the byte the DMA is moving, and
+//     writes are lost; VRAM has its own bus, as does WRAM on CGB
+//   - IO registers and HRAM stay reachable, which is why games run their DMA
+//     wait loop from HRAM
+// Writing 0xFF46 again restarts the transfer; the old one keeps going
+// through the new one's setup M-cycle.
+
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// Bytes copied per transfer
+pub const OAM_DMA_LENGTH: u8 = 160;
+
+/// The memory bus an address is on, as seen by OAM DMA conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DmaBus {
+    External,
+    Vram,
+    Wram,
+}
+
+impl DmaBus {
+    fn of(addr: u16, cgb: bool) -> DmaBus {
+        match addr {
+            0x8000..=0x9FFF => DmaBus::Vram,
+            0xC000..=0xFDFF if cgb => DmaBus::Wram,
+            _ => DmaBus::External,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OamDma {
+    // Start of the page being copied
+    source: u16,
+    // Next byte to copy; OAM_DMA_LENGTH once the transfer is done
+    index: u8,
+    // Source page of a transfer in its setup M-cycle
+    pending: Option<u16>,
+    // A byte was copied in the last M-cycle, so the DMA holds the bus
+    busy: bool,
+    // Byte copied in the last M-cycle, seen by conflicting CPU reads
+    last: u8,
+    // T-cycles short of a whole M-cycle
+    clock: u32,
+}
+
+impl OamDma {
+    pub fn new() -> Self {
+        OamDma {
+            index: OAM_DMA_LENGTH,
+            ..Default::default()
+        }
+    }
+
+    /// A write to 0xFF46: start copying from `page` << 8 after a setup M-cycle.
+    pub fn start(&mut self, page: u8) {
+        if !self.active() {
+            self.clock = 0;
+        }
+        // 0xE000 and up reads echo RAM, like the CPU would
+        let source = (page as u16) << 8;
+        self.pending = Some(if source >= 0xE000 {
+            source - 0x2000
+        } else {
+            source
+        });
+    }
+
+    /// Whether a transfer is starting or under way.
+    pub fn active(&self) -> bool {
+        self.pending.is_some() || self.index < OAM_DMA_LENGTH || self.busy
+    }
+
+    /// Whole M-cycles in the next `cycles` T-cycles.
+    pub fn m_cycles(&mut self, cycles: u32) -> u32 {
+        self.clock += cycles;
+        let m = self.clock / 4;
+        self.clock %= 4;
+        m
+    }
+
+    /// Run one M-cycle. Returns the source address and OAM offset of the byte
+    /// to copy in it, if any; report the byte back with `copied`.
+    pub fn m_cycle(&mut self) -> Option<(u16, u8)> {
+        let copy = if self.index < OAM_DMA_LENGTH {
+            let copy = (self.source.wrapping_add(self.index as u16), self.index);
+            self.index += 1;
+            Some(copy)
+        } else {
+            None
+        };
+        self.busy = copy.is_some();
+        if let Some(source) = self.pending.take() {
+            self.source = source;
+            self.index = 0;
+        }
+        copy
+    }
+
+    /// The byte moved by the current M-cycle's copy.
+    pub fn copied(&mut self, value: u8) {
+        self.last = value;
+    }
+
+    /// What a CPU read of `addr` returns while the DMA holds its bus, or None
+    /// if the access goes through. Writes are lost whenever this is Some.
+    pub fn conflict(&self, addr: u16, cgb: bool) -> Option<u8> {
+        if !self.busy {
+            return None;
+        }
+        match addr {
+            0xFE00..=0xFEFF => Some(0xFF),
+            0xFF00..=0xFFFF => None,
+            _ if DmaBus::of(addr, cgb) == DmaBus::of(self.source, cgb) => Some(self.last),
+            _ => None,
+        }
+    }
+
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.u16(self.source);
+        w.u8(self.index);
+        w.bool(self.pending.is_some());
+        w.u16(self.pending.unwrap_or(0));
+        w.bool(self.busy);
+        w.u8(self.last);
+        w.u8(self.clock as u8);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.source = r.u16()?;
+        self.index = r.u8()?;
+        if self.index > OAM_DMA_LENGTH {
+            return Err(StateError::Invalid("OAM DMA position"));
+        }
+        let pending = r.bool()?;
+        let page = r.u16()?;
+        self.pending = pending.then_some(page);
+        self.busy = r.bool()?;
+        self.last = r.u8()?;
+        self.clock = (r.u8()? & 0x03) as u32;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cpu::Cpu;
+    use crate::gpu::Gpu;
+    use crate::memory::Memory;
+
+    /// A transfer from `page` past its setup M-cycle, `copied` bytes in.
+    fn transfer(page: u8, copied: u8) -> OamDma {
+        let mut dma = OamDma::new();
+        dma.start(page);
+        assert_eq!(dma.m_cycle(), None);
+        for _ in 0..copied {
+            dma.m_cycle();
+        }
+        dma
+    }
+
+    #[test]
+    fn oam_dma_copies_a_byte_per_m_cycle_and_holds_the_bus() {
+        let mut cpu = Cpu::initialize();
+        let memory = Memory::initialize_with_rom(vec![0; 0x8000]).unwrap();
+        let mut bus = Bus::new(Box::new(memory), Gpu::initialize());
+        for i in 0..160u16 {
+            bus.memory.write_byte(0xC100 + i, i as u8 + 1);
+        }
+        // Run from HRAM, the only memory besides IO the CPU keeps during DMA
+        let program = [
+            0xE0, 0x46, // LDH (46),A
+            0xFA, 0x00, 0xFE, // LD A,(FE00)
+            0x47, // LD B,A
+            0xFA, 0x50, 0xC1, // LD A,(C150)
+            0x18, 0xFE, // JR -2
+        ];
+        for (i, byte) in program.iter().enumerate() {
+            bus.memory.write_byte(0xFF80 + i as u16, *byte);
+        }
+        cpu.registers_mut().program_counter = 0xFF80;
+        cpu.registers_mut().a = 0xC1;
+        for _ in 0..4 {
+            cpu.step(&mut bus);
+        }
+        // OAM is locked; WRAM, on the source's bus, yields the byte in flight
+        // (byte 7, three M-cycles after the read of OAM copied byte 2)
+        assert_eq!(cpu.registers().b, 0xFF);
+        assert_eq!(cpu.registers().a, 8);
+        assert_eq!(bus.memory.read_byte(0xFE08), 0);
+
+        while bus.memory.oam_dma_active() {
+            cpu.step(&mut bus);
+        }
+        for i in 0..160u16 {
+            assert_eq!(bus.memory.read_byte(0xFE00 + i), i as u8 + 1);
+        }
+    }
+
+    #[test]
+    fn second_write_restarts_after_the_old_transfer_runs_through_setup() {
+        let mut dma = transfer(0xC1, 10);
+        dma.start(0xD0);
+        // The new transfer's setup M-cycle still copies for the old one
+        assert_eq!(dma.m_cycle(), Some((0xC10A, 10)));
+        assert_eq!(dma.m_cycle(), Some((0xD000, 0)));
+        for index in 1..OAM_DMA_LENGTH {
+            assert_eq!(dma.m_cycle(), Some((0xD000 + index as u16, index)));
+        }
+        assert_eq!(dma.m_cycle(), None);
+        assert!(!dma.active());
+    }
+
+    #[test]
+    fn cgb_wram_is_on_its_own_bus() {
+        // From WRAM: the rest of WRAM conflicts on both models, ROM only on DMG
+        let mut dma = transfer(0xC1, 1);
+        dma.copied(0x42);
+        assert_eq!(dma.conflict(0xD000, true), Some(0x42));
+        assert_eq!(dma.conflict(0xD000, false), Some(0x42));
+        assert_eq!(dma.conflict(0x4000, true), None);
+        assert_eq!(dma.conflict(0x4000, false), Some(0x42));
+
+        // From ROM: WRAM stays reachable on CGB only
+        let mut dma = transfer(0x40, 1);
+        dma.copied(0x42);
+        assert_eq!(dma.conflict(0xC000, true), None);
+        assert_eq!(dma.conflict(0xC000, false), Some(0x42));
+        assert_eq!(dma.conflict(0x8000, true), None);
+        assert_eq!(dma.conflict(0xFE00, true), Some(0xFF));
+        assert_eq!(dma.conflict(0xFF80, true), None);
+    }
+}
@@ -0,0 +1,361 @@
+// Game Boy — the whole machine behind one type, for frontends and tools.
+//
+// `GameBoy` owns the CPU and the bus, and through the bus the PPU, APU,
+// memory, cartridge and IO blocks. Callers drive it a frame at a time
+// (`run_frame`) or an instruction at a time (`step_instruction`), feed it
+// button state, and collect the finished frame and audio samples. The SDL
+// window, the headless runner, the wasm bindings, the test ROM harness and
+// the benchmark all run the machine through it.
+//
+// The parts stay reachable (`cpu`, `memory`, `bus_mut`) for debug views and
+// test harnesses that need to look inside.
+
+use crate::boot::{self, Boot, BootError, Model};
+use crate::bus::{BlockedAccess, Bus};
+use crate::cartridge::CartridgeError;
+use crate::cpu::Cpu;
+use crate::gpu::{Framebuffer, Gpu, Renderer};
+use crate::header::Header;
+use crate::joypad::Button;
+use crate::memory::{Memory, MemoryAccess};
+use crate::savestate::{self, StateError};
+use crate::serial::SerialDevice;
+use std::fmt;
+
+/// T-cycles in one frame (154 lines × 456). With the LCD off the PPU never
+/// finishes a frame, so `run_frame` also stops after this many cycles.
+pub const CYCLES_PER_FRAME: u32 = 70_224;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameBoyError {
+    /// The ROM is not a cartridge this emulator can run.
+    Cartridge(CartridgeError),
+    /// The boot ROM was rejected.
+    Boot(BootError),
+    /// A save state could not be restored.
+    State(StateError),
+}
+
+impl fmt::Display for GameBoyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameBoyError::Cartridge(e) => write!(f, "{}", e),
+            GameBoyError::Boot(e) => write!(f, "{}", e),
+            GameBoyError::State(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for GameBoyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameBoyError::Cartridge(e) => Some(e),
+            GameBoyError::Boot(e) => Some(e),
+            GameBoyError::State(e) => Some(e),
+        }
+    }
+}
+
+impl From<CartridgeError> for GameBoyError {
+    fn from(e: CartridgeError) -> Self {
+        GameBoyError::Cartridge(e)
+    }
+}
+
+impl From<BootError> for GameBoyError {
+    fn from(e: BootError) -> Self {
+        GameBoyError::Boot(e)
+    }
+}
+
+impl From<StateError> for GameBoyError {
+    fn from(e: StateError) -> Self {
+        GameBoyError::State(e)
+    }
+}
+
+/// What one `step_instruction` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// T-cycles taken.
+    pub cycles: u32,
+    /// An interrupt was dispatched instead of running an instruction; PC is
+    /// at its vector.
+    pub interrupt: bool,
+    /// The PPU finished a frame; it is now in `framebuffer`.
+    pub frame_completed: bool,
+}
+
+pub struct GameBoy {
+    cpu: Cpu,
+    bus: Bus,
+    header: Header,
+    // Last finished frame; the PPU's blank buffer until the first one
+    framebuffer: Framebuffer,
+}
+
+impl GameBoy {
+    /// Load `rom` and power on. With `boot` None the machine starts at
+    /// 0x0100 in the post-boot state of a CGB for cartridges with CGB
+    /// support, and of a DMG otherwise.
+    pub fn new(rom: Vec<u8>, boot: Option<Boot>) -> Result<GameBoy, GameBoyError> {
+        let mut memory = Memory::initialize_with_rom(rom)?;
+        let header = memory.header().clone();
+        let boot = boot.unwrap_or_else(|| Boot::Skip(Model::for_header(&header)));
+        let mut cpu = Cpu::initialize();
+        boot::power_on(boot, &mut cpu, &mut memory)?;
+        let gpu = Gpu::initialize();
+        let framebuffer = gpu.framebuffer().clone();
+        Ok(GameBoy {
+            cpu,
+            bus: Bus::new(Box::new(memory), gpu),
+            header,
+            framebuffer,
+        })
+    }
+
+    /// The cartridge header, as parsed when the ROM was loaded.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Run until the PPU finishes a frame, or for one frame's worth of cycles
+    /// while the LCD is off. Returns whether a new frame is in `framebuffer`.
+    pub fn run_frame(&mut self) -> bool {
+        let mut cycles = 0;
+        while cycles < CYCLES_PER_FRAME {
+            let step = self.step_instruction();
+            if step.frame_completed {
+                return true;
+            }
+            cycles += step.cycles;
+        }
+        false
+    }
+
+    /// Dispatch a pending interrupt, or else run one instruction (or one idle
+    /// M-cycle while halted or stopped). The PPU, APU, timer and serial port
+    /// are clocked along with it.
+    pub fn step_instruction(&mut self) -> Step {
+        let dispatch = self.cpu.handle_interrupts(&mut self.bus);
+        if dispatch.t > 0 {
+            return self.finish_step(dispatch.t as u32, true);
+        }
+        let step = self.cpu.step(&mut self.bus);
+        self.finish_step(step.t as u32, false)
+    }
+
+    /// `step_instruction`, plus a log line describing what ran.
+    pub fn step_instruction_logged(&mut self) -> (Step, String) {
+        let dispatch = self.cpu.handle_interrupts(&mut self.bus);
+        if dispatch.t > 0 {
+            let pc = self.cpu.registers().program_counter;
+            let log = format!("0x{:04X}: interrupt", pc);
+            return (self.finish_step(dispatch.t as u32, true), log);
+        }
+        let (step, log) = self.cpu.step_logged(&mut self.bus);
+        (self.finish_step(step.t as u32, false), log)
+    }
+
+    fn finish_step(&mut self, cycles: u32, interrupt: bool) -> Step {
+        let frame = self.bus.take_frame();
+        let frame_completed = frame.is_some();
+        if let Some(framebuffer) = frame {
+            self.framebuffer = framebuffer;
+        }
+        Step {
+            cycles,
+            interrupt,
+            frame_completed,
+        }
+    }
+
+    /// The last frame the PPU finished.
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// Audio produced since the last call, interleaved stereo [L, R, L, R, ...]
+    /// at `apu::SAMPLE_RATE`.
+    pub fn take_samples(&mut self) -> Vec<i16> {
+        self.bus.take_samples()
+    }
+
+    /// Press or release one button.
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        self.bus.memory.set_button(button, pressed);
+    }
+
+    /// Set every button at once from a `Button::mask` bitmask.
+    pub fn set_buttons(&mut self, held: u8) {
+        self.bus.memory.set_held_buttons(held);
+    }
+
+    /// Buttons currently held, as a `Button::mask` bitmask.
+    pub fn buttons(&self) -> u8 {
+        self.bus.memory.held_buttons()
+    }
+
+    /// Attach a device to the other end of the link cable.
+    pub fn set_serial_device(&mut self, device: Box<dyn SerialDevice>) {
+        self.bus.memory.set_serial_device(device);
+    }
+
+    /// Switch PPU renderer.
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.bus.gpu.set_renderer(renderer);
+    }
+
+    /// Turn recording of blocked VRAM and OAM accesses on or off.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.bus.set_strict(strict);
+    }
+
+    /// Accesses blocked since the last call, oldest first (strict mode only).
+    pub fn take_blocked(&mut self) -> Vec<BlockedAccess> {
+        self.bus.take_blocked()
+    }
+
+    /// Battery-backed cartridge RAM (plus RTC block) in `.sav` format, or
+    /// None when the cartridge has no battery.
+    pub fn save_data(&self) -> Option<Vec<u8>> {
+        self.bus.memory.save_data()
+    }
+
+    /// Restore battery-backed cartridge RAM from a `.sav` file.
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        self.bus.memory.load_save_data(data);
+    }
+
+    /// Whether the cartridge's rumble motor is on.
+    pub fn rumble(&self) -> bool {
+        self.bus.memory.rumble()
+    }
+
+    /// Snapshot the whole machine.
+    pub fn save_state(&self) -> Vec<u8> {
+        savestate::save_state(&self.cpu, &self.bus.gpu, self.bus.memory.as_ref())
+    }
+
+    /// Restore a snapshot from `save_state`. The buttons held right now stay
+    /// held, rather than the ones held when the state was saved.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), GameBoyError> {
+        let held = self.buttons();
+        let result = savestate::load_state(
+            data,
+            &mut self.cpu,
+            &mut self.bus.gpu,
+            self.bus.memory.as_mut(),
+        );
+        self.set_buttons(held);
+        result?;
+        Ok(())
+    }
+
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    pub fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+
+    /// Untimed view of memory: reads and writes do not clock anything or
+    /// respect PPU and DMA lockouts.
+    pub fn memory(&self) -> &dyn MemoryAccess {
+        self.bus.memory.as_ref()
+    }
+
+    pub fn memory_mut(&mut self) -> &mut dyn MemoryAccess {
+        self.bus.memory.as_mut()
+    }
+
+    /// The bus, for timed CPU-style accesses.
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        &mut self.bus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A DMG past its boot ROM, with the LCD on, spinning on JR -2 at 0x0100.
+    fn spinning() -> GameBoy {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0100..0x0102].copy_from_slice(&[0x18, 0xFE]);
+        GameBoy::new(rom, Some(Boot::Skip(Model::Dmg))).unwrap()
+    }
+
+    #[test]
+    fn run_frame_stops_at_each_vblank() {
+        let mut gb = spinning();
+        for _ in 0..3 {
+            assert!(gb.run_frame());
+            assert_eq!(gb.memory().read_byte(0xFF44), 144);
+        }
+        // The next frame is one frame's worth of cycles on, give or take a JR
+        let mut cycles = 0;
+        loop {
+            let step = gb.step_instruction();
+            cycles += step.cycles;
+            if step.frame_completed {
+                break;
+            }
+        }
+        assert!(cycles.abs_diff(CYCLES_PER_FRAME) < 12, "{}", cycles);
+
+        gb.memory_mut().write_byte(0xFF40, 0x00);
+        assert!(!gb.run_frame());
+    }
+
+    #[test]
+    fn set_buttons_reach_p1_and_raise_the_joypad_interrupt() {
+        let mut gb = spinning();
+        gb.memory_mut().write_byte(0xFF0F, 0x00);
+        gb.set_buttons(Button::A.mask() | Button::Down.mask());
+        assert_eq!(gb.buttons(), 0x18);
+        assert_eq!(gb.memory().read_byte(0xFF0F) & 0x10, 0x10);
+        gb.memory_mut().write_byte(0xFF00, 0x10);
+        assert_eq!(gb.memory().read_byte(0xFF00), 0xDE);
+        gb.memory_mut().write_byte(0xFF00, 0x20);
+        assert_eq!(gb.memory().read_byte(0xFF00), 0xE7);
+    }
+
+    #[test]
+    fn load_state_keeps_the_buttons_held_now() {
+        let mut gb = spinning();
+        gb.set_button(Button::A, true);
+        let state = gb.save_state();
+        gb.set_buttons(Button::Start.mask());
+        gb.load_state(&state).unwrap();
+        assert_eq!(gb.buttons(), Button::Start.mask());
+        gb.memory_mut().write_byte(0xFF00, 0x10);
+        assert_eq!(gb.memory().read_byte(0xFF00), 0xD7);
+    }
+
+    #[test]
+    fn new_reports_what_it_rejected() {
+        let err = GameBoy::new(vec![0; 0x100], None).err();
+        assert_eq!(
+            err,
+            Some(GameBoyError::Cartridge(CartridgeError::TooSmall(0x100)))
+        );
+
+        let mut rom = vec![0; 0x8000];
+        rom[0x0147] = 0xFF;
+        let err = GameBoy::new(rom, None).err();
+        assert_eq!(
+            err,
+            Some(GameBoyError::Cartridge(CartridgeError::UnsupportedType(
+                0xFF
+            )))
+        );
+
+        let err = GameBoy::new(vec![0; 0x8000], Some(Boot::Bios(vec![0; 0x200]))).err();
+        assert_eq!(
+            err,
+            Some(GameBoyError::Boot(BootError::InvalidBiosSize(0x200)))
+        );
+    }
+}
@@ -0,0 +1,612 @@
+// GDB remote serial protocol stub — lets gdb, or any tool speaking RSP, drive
+// the machine over TCP.
+//
+// The stub wraps a `Debugger`: software breakpoints (Z0/Z1) and watchpoints
+// (Z2–Z4) become its breakpoints and watchpoints, `s` is a step-in and `c`
+// resumes. Frontends call `run_frame` once per frame in place of
+// `GameBoy::run_frame`; it answers whatever packets have arrived, then runs a
+// frame unless the debugger is paused.
+//
+// Registers go over the wire as six 16-bit little-endian pairs in the order
+// AF BC DE HL SP PC (numbers 0–5), the first six registers of gdb's z80
+// target. Memory is read and written through `MemoryAccess`, so it is the
+// CPU's view with banking applied, and writes to ROM reach the mapper.
+//
+// Supported packets: ? g G p P m M c s Z z D k, QStartNoAckMode, qSupported
+// and the thread queries gdb sends on attach; anything else gets the empty
+// "unsupported" reply.
+
+use crate::debugger::{Access, Break, Debugger, Register, StepMode, Watchpoint};
+use crate::GameBoy;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Registers in `g`/`G` order.
+const REGISTERS: [Register; 6] = [
+    Register::AF,
+    Register::BC,
+    Register::DE,
+    Register::HL,
+    Register::SP,
+    Register::PC,
+];
+
+/// How long `run_frame` waits for a packet while the machine is paused, so
+/// callers without frame pacing do not spin.
+const PAUSED_POLL: Duration = Duration::from_millis(16);
+
+// Signals in stop replies
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// What one `GdbStub::run_frame` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdbStatus {
+    /// The machine ran; `frame_completed` as for `GameBoy::run_frame`.
+    Running { frame_completed: bool },
+    /// The machine is stopped, waiting for gdb.
+    Paused,
+    /// gdb detached or the connection closed. Breakpoints are cleared, and
+    /// the machine should carry on without the stub.
+    Detached,
+    /// gdb sent a kill request.
+    Killed,
+}
+
+/// A gdb breakpoint or watchpoint, by its Z packet type.
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    kind: u8,
+    addr: u16,
+    length: u16,
+    id: u32,
+}
+
+pub struct GdbStub {
+    stream: TcpStream,
+    debugger: Debugger,
+    points: Vec<Point>,
+    // Bytes received but not yet parsed into packets
+    input: Vec<u8>,
+    // Acks turned off with QStartNoAckMode
+    no_ack: bool,
+    // Reply to `?`
+    last_stop: String,
+    status: GdbStatus,
+}
+
+impl GdbStub {
+    /// Wait for gdb to connect on `addr`. The machine starts paused.
+    pub fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<GdbStub> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+        GdbStub::new(stream)
+    }
+
+    /// Serve gdb on an accepted connection. The machine starts paused.
+    pub fn new(stream: TcpStream) -> io::Result<GdbStub> {
+        stream.set_nodelay(true)?;
+        let mut debugger = Debugger::new();
+        debugger.pause();
+        Ok(GdbStub {
+            stream,
+            debugger,
+            points: Vec::new(),
+            input: Vec::new(),
+            no_ack: false,
+            last_stop: format!("S{:02x}", SIGTRAP),
+            status: GdbStatus::Paused,
+        })
+    }
+
+    /// Answer the packets received since the last call, then run a frame
+    /// unless the machine is paused. While paused this waits briefly for a
+    /// packet.
+    pub fn run_frame(&mut self, gb: &mut GameBoy) -> GdbStatus {
+        if matches!(self.status, GdbStatus::Detached | GdbStatus::Killed) {
+            return self.status;
+        }
+        let timeout = self.debugger.paused().then_some(PAUSED_POLL);
+        if let Err(e) = self.receive(gb, timeout) {
+            if e.kind() != io::ErrorKind::WouldBlock && e.kind() != io::ErrorKind::TimedOut {
+                self.detach(gb);
+            }
+        }
+        if matches!(self.status, GdbStatus::Detached | GdbStatus::Killed) {
+            return self.status;
+        }
+        if let Some(stop) = self.debugger.run_frame(gb) {
+            let reply = self.stop_reply(&stop);
+            self.stop(&reply);
+        }
+        self.status = if self.debugger.paused() {
+            GdbStatus::Paused
+        } else {
+            GdbStatus::Running {
+                frame_completed: self.debugger.frame_completed(),
+            }
+        };
+        self.status
+    }
+
+    /// Read what has arrived, waiting up to `timeout` for it (`None` = don't
+    /// block), and handle every complete packet.
+    fn receive(&mut self, gb: &mut GameBoy, timeout: Option<Duration>) -> io::Result<()> {
+        match timeout {
+            Some(t) => {
+                self.stream.set_nonblocking(false)?;
+                self.stream.set_read_timeout(Some(t))?;
+            }
+            None => self.stream.set_nonblocking(true)?,
+        }
+        let mut buffer = [0u8; 4096];
+        let count = self.stream.read(&mut buffer)?;
+        if count == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.input.extend_from_slice(&buffer[..count]);
+        while let Some(packet) = self.next_packet()? {
+            if let Some(reply) = self.handle(gb, &packet) {
+                self.send(&reply)?;
+            }
+            if matches!(self.status, GdbStatus::Detached | GdbStatus::Killed) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the next packet out of `input`, acking it. A Ctrl-C (0x03) is
+    /// handled on the spot.
+    fn next_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let Some(&first) = self.input.first() else {
+                return Ok(None);
+            };
+            match first {
+                b'$' => {}
+                0x03 => {
+                    self.input.remove(0);
+                    if !self.debugger.paused() {
+                        self.debugger.pause();
+                        self.stop(&format!("S{:02x}", SIGINT));
+                    }
+                    continue;
+                }
+                // Acks, and noise between packets
+                _ => {
+                    self.input.remove(0);
+                    continue;
+                }
+            }
+            let Some(end) = self.input.iter().position(|&b| b == b'#') else {
+                return Ok(None);
+            };
+            if self.input.len() < end + 3 {
+                return Ok(None);
+            }
+            let data: Vec<u8> = self.input[1..end].to_vec();
+            let checksum = std::str::from_utf8(&self.input[end + 1..end + 3])
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+            self.input.drain(..end + 3);
+            if !self.no_ack {
+                let valid = checksum == Some(data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)));
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+                if !valid {
+                    continue;
+                }
+            }
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let checksum = data.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        let packet = format!("${}#{:02x}", data, checksum);
+        self.stream.write_all(packet.as_bytes())
+    }
+
+    /// Report a stop to gdb and remember it for `?`.
+    fn stop(&mut self, reply: &str) {
+        self.last_stop = reply.to_string();
+        if self.send(reply).is_err() {
+            self.status = GdbStatus::Detached;
+        }
+    }
+
+    fn stop_reply(&self, stop: &Break) -> String {
+        match stop {
+            Break::Watchpoint { id, hit } => {
+                let kind = match self.points.iter().find(|p| p.id == *id).map(|p| p.kind) {
+                    Some(4) => "awatch",
+                    _ if hit.access == Access::Read => "rwatch",
+                    _ => "watch",
+                };
+                format!("T{:02x}{}:{:x};", SIGTRAP, kind, hit.addr)
+            }
+            Break::Pause { .. } => format!("S{:02x}", SIGINT),
+            Break::UnimplementedOpcode { .. } => format!("S{:02x}", SIGILL),
+            _ => format!("S{:02x}", SIGTRAP),
+        }
+    }
+
+    /// Carry out one packet. Returns the reply, or None when there is none
+    /// yet (continue and step answer with a stop reply later).
+    fn handle(&mut self, gb: &mut GameBoy, packet: &str) -> Option<String> {
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => self.last_stop.clone(),
+            "g" => {
+                let r = gb.cpu().registers();
+                REGISTERS
+                    .iter()
+                    .map(|reg| hex_bytes(&reg.read(r).to_le_bytes()))
+                    .collect()
+            }
+            "G" => match decode_hex(args) {
+                Some(bytes) if bytes.len() >= 2 * REGISTERS.len() => {
+                    let r = gb.cpu_mut().registers_mut();
+                    for (reg, pair) in REGISTERS.iter().zip(bytes.chunks(2)) {
+                        reg.write(r, u16::from_le_bytes([pair[0], pair[1]]));
+                    }
+                    "OK".to_string()
+                }
+                _ => "E01".to_string(),
+            },
+            "p" => match parse_hex(args).and_then(|n| REGISTERS.get(n as usize)) {
+                Some(reg) => hex_bytes(&reg.read(gb.cpu().registers()).to_le_bytes()),
+                None => "E01".to_string(),
+            },
+            "P" => {
+                let set = args.split_once('=').and_then(|(n, value)| {
+                    let reg = REGISTERS.get(parse_hex(n)? as usize)?;
+                    let bytes = decode_hex(value).filter(|b| b.len() == 2)?;
+                    Some((reg, u16::from_le_bytes([bytes[0], bytes[1]])))
+                });
+                match set {
+                    Some((reg, value)) => {
+                        reg.write(gb.cpu_mut().registers_mut(), value);
+                        "OK".to_string()
+                    }
+                    None => "E01".to_string(),
+                }
+            }
+            "m" => match parse_range(args) {
+                Some((addr, length)) => {
+                    let memory = gb.memory();
+                    (0..length)
+                        .map(|i| format!("{:02x}", memory.read_byte(addr.wrapping_add(i))))
+                        .collect()
+                }
+                None => "E01".to_string(),
+            },
+            "M" => {
+                let write = args.split_once(':').and_then(|(range, data)| {
+                    let (addr, length) = parse_range(range)?;
+                    decode_hex(data)
+                        .filter(|bytes| bytes.len() == length as usize)
+                        .map(|bytes| (addr, bytes))
+                });
+                match write {
+                    Some((addr, bytes)) => {
+                        let memory = gb.memory_mut();
+                        for (i, byte) in bytes.into_iter().enumerate() {
+                            memory.write_byte(addr.wrapping_add(i as u16), byte);
+                        }
+                        "OK".to_string()
+                    }
+                    None => "E01".to_string(),
+                }
+            }
+            "c" | "s" => {
+                if let Some(addr) = parse_hex(args) {
+                    gb.cpu_mut().registers_mut().program_counter = addr as u16;
+                }
+                if command == "c" {
+                    self.debugger.resume();
+                } else {
+                    self.debugger.step(gb, StepMode::In);
+                }
+                return None;
+            }
+            "Z" | "z" => self.point(command == "Z", args),
+            "D" => {
+                self.detach(gb);
+                "OK".to_string()
+            }
+            "k" => {
+                self.status = GdbStatus::Killed;
+                return None;
+            }
+            "H" | "T" => "OK".to_string(),
+            "q" | "Q" => self.query(packet),
+            _ => String::new(),
+        };
+        Some(reply)
+    }
+
+    /// Insert (`Z`) or remove (`z`) a breakpoint or watchpoint.
+    fn point(&mut self, insert: bool, args: &str) -> String {
+        let mut fields = args.split(',');
+        let (Some(kind), Some(addr), Some(length)) = (
+            fields.next().and_then(parse_hex),
+            fields.next().and_then(parse_hex),
+            fields.next().and_then(parse_hex),
+        ) else {
+            return "E01".to_string();
+        };
+        let (addr, length) = (addr as u16, (length as u16).max(1));
+        let kinds = match kind {
+            0 | 1 => "",
+            2 => "w",
+            3 => "r",
+            4 => "rw",
+            _ => return String::new(),
+        };
+        let existing = self
+            .points
+            .iter()
+            .position(|p| p.kind == kind as u8 && p.addr == addr && p.length == length);
+        match (insert, existing) {
+            (true, Some(_)) => {}
+            (true, None) => {
+                let id = if kinds.is_empty() {
+                    self.debugger.add_breakpoint(addr, None)
+                } else {
+                    let end = addr.saturating_add(length - 1);
+                    match Watchpoint::new(addr, end, kinds) {
+                        Ok(watchpoint) => self.debugger.add_watchpoint(watchpoint),
+                        Err(_) => return "E01".to_string(),
+                    }
+                };
+                self.points.push(Point {
+                    kind: kind as u8,
+                    addr,
+                    length,
+                    id,
+                });
+            }
+            (false, Some(index)) => {
+                let point = self.points.remove(index);
+                let _ = self.debugger.remove(point.id);
+            }
+            (false, None) => return "E01".to_string(),
+        }
+        "OK".to_string()
+    }
+
+    fn query(&mut self, packet: &str) -> String {
+        let name = packet.split([':', ',']).next().unwrap_or("");
+        match name {
+            "qSupported" => "PacketSize=4000;QStartNoAckMode+".to_string(),
+            "QStartNoAckMode" => {
+                self.no_ack = true;
+                "OK".to_string()
+            }
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Drop every breakpoint and let the machine run free.
+    fn detach(&mut self, gb: &mut GameBoy) {
+        self.debugger.clear();
+        self.debugger.resume();
+        self.points.clear();
+        gb.bus_mut().set_watchpoints(Vec::new());
+        self.status = GdbStatus::Detached;
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 16).ok()
+}
+
+/// `<addr>,<length>` as in m and M packets.
+fn parse_range(text: &str) -> Option<(u16, u16)> {
+    let (addr, length) = text.split_once(',')?;
+    Some((parse_hex(addr)? as u16, parse_hex(length)? as u16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debugger::tests::machine;
+
+    /// The debugger tests' `machine`, paused with gdb attached.
+    fn attach() -> (GameBoy, GdbStub, TcpStream) {
+        let gb = machine();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let stub = GdbStub::new(listener.accept().unwrap().0).unwrap();
+        (gb, stub, client)
+    }
+
+    /// `data` framed as a packet. Sent with one write, so the stub sees it
+    /// whole.
+    fn packet(data: &str) -> Vec<u8> {
+        let checksum = data.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        format!("${}#{:02x}", data, checksum).into_bytes()
+    }
+
+    /// The next reply's data, skipping acks and checking its checksum.
+    fn reply(client: &mut TcpStream) -> String {
+        let mut byte = [0u8];
+        while byte[0] != b'$' {
+            client.read_exact(&mut byte).unwrap();
+        }
+        let mut data = Vec::new();
+        loop {
+            client.read_exact(&mut byte).unwrap();
+            if byte[0] == b'#' {
+                break;
+            }
+            data.push(byte[0]);
+        }
+        let mut checksum = [0u8; 2];
+        client.read_exact(&mut checksum).unwrap();
+        let sum = data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        assert_eq!(checksum, format!("{:02x}", sum).as_bytes());
+        String::from_utf8(data).unwrap()
+    }
+
+    /// Send a packet, let the stub answer it, and read the reply.
+    fn exchange(
+        data: &str,
+        gb: &mut GameBoy,
+        stub: &mut GdbStub,
+        client: &mut TcpStream,
+    ) -> (String, GdbStatus) {
+        client.write_all(&packet(data)).unwrap();
+        let status = stub.run_frame(gb);
+        (reply(client), status)
+    }
+
+    #[test]
+    fn packets_are_acked_by_checksum() {
+        let (mut gb, mut stub, mut client) = attach();
+        client.write_all(b"$?#00").unwrap();
+        stub.run_frame(&mut gb);
+        let mut ack = [0u8];
+        client.read_exact(&mut ack).unwrap();
+        assert_eq!(&ack, b"-");
+
+        client.write_all(b"$?#3f").unwrap();
+        stub.run_frame(&mut gb);
+        let mut answer = [0u8; 8];
+        client.read_exact(&mut answer).unwrap();
+        assert_eq!(&answer, b"+$S05#b8");
+    }
+
+    #[test]
+    fn packet_split_across_reads_is_reassembled() {
+        let (mut gb, mut stub, mut client) = attach();
+        let request = packet("m100,1");
+        client.write_all(&request[..4]).unwrap();
+        stub.run_frame(&mut gb);
+        client.write_all(&request[4..]).unwrap();
+        stub.run_frame(&mut gb);
+        assert_eq!(reply(&mut client), "c3");
+    }
+
+    #[test]
+    fn no_ack_mode_stops_acks() {
+        let (mut gb, mut stub, mut client) = attach();
+        let (answer, _) = exchange("QStartNoAckMode", &mut gb, &mut stub, &mut client);
+        assert_eq!(answer, "OK");
+        client.write_all(&packet("?")).unwrap();
+        stub.run_frame(&mut gb);
+        let mut first = [0u8];
+        client.read_exact(&mut first).unwrap();
+        assert_eq!(&first, b"$");
+    }
+
+    #[test]
+    fn registers_go_af_bc_de_hl_sp_pc_little_endian() {
+        let (mut gb, mut stub, mut client) = attach();
+        let values = [0x1230, 0x3456, 0x789A, 0xBCDE, 0xDFF0, 0x0150];
+        let r = gb.cpu_mut().registers_mut();
+        for (reg, value) in REGISTERS.iter().zip(values) {
+            reg.write(r, value);
+        }
+        let mut rsp = |data: &str| exchange(data, &mut gb, &mut stub, &mut client).0;
+        assert_eq!(rsp("g"), "301256349a78debcf0df5001");
+        assert_eq!(rsp("p3"), "debc");
+        assert_eq!(rsp("p6"), "E01");
+
+        assert_eq!(rsp("G00a0adde0df0efbe00d00002"), "OK");
+        assert_eq!(rsp("g"), "00a0adde0df0efbe00d00002");
+        assert_eq!(rsp("G00a0"), "E01");
+        assert_eq!(rsp("P1=3412"), "OK");
+        assert_eq!(rsp("p1"), "3412");
+        assert_eq!(rsp("P1=34"), "E01");
+
+        let r = gb.cpu().registers();
+        let read: Vec<u16> = REGISTERS.iter().map(|reg| reg.read(r)).collect();
+        assert_eq!(read, [0xA000, 0x1234, 0xF00D, 0xBEEF, 0xD000, 0x0200]);
+    }
+
+    #[test]
+    fn memory_writes_read_back() {
+        let (mut gb, mut stub, mut client) = attach();
+        let mut rsp = |data: &str| exchange(data, &mut gb, &mut stub, &mut client).0;
+        assert_eq!(rsp("Mc000,3:a1b2c3"), "OK");
+        assert_eq!(rsp("mc000,3"), "a1b2c3");
+        assert_eq!(rsp("mc000,0"), "");
+        assert_eq!(rsp("Mc000,2:a1b2c3"), "E01");
+        assert_eq!(rsp("mc000"), "E01");
+        assert_eq!(gb.memory().read_byte(0xC001), 0xB2);
+    }
+
+    #[test]
+    fn breakpoints_are_inserted_and_removed() {
+        let (mut gb, mut stub, mut client) = attach();
+        let mut rsp = |data: &str| exchange(data, &mut gb, &mut stub, &mut client);
+        assert_eq!(rsp("Z0,150,1").0, "OK");
+        assert_eq!(rsp("Z0,150,1").0, "OK");
+        assert_eq!(rsp("c"), ("S05".to_string(), GdbStatus::Paused));
+        assert_eq!(rsp("p5").0, "5001");
+        assert_eq!(rsp("z0,150,1").0, "OK");
+        assert_eq!(rsp("z0,150,1").0, "E01");
+        assert_eq!(rsp("Z0,153,1").0, "OK");
+        // The breakpoint at 0150 is gone, so this stops after the call
+        assert_eq!(rsp("c").0, "S05");
+        assert_eq!(rsp("p5").0, "5301");
+    }
+
+    #[test]
+    fn step_and_continue_answer_with_stop_replies() {
+        let (mut gb, mut stub, mut client) = attach();
+        let mut rsp = |data: &str| exchange(data, &mut gb, &mut stub, &mut client);
+        assert_eq!(rsp("s"), ("S05".to_string(), GdbStatus::Paused));
+        assert_eq!(rsp("p5").0, "5001");
+        assert_eq!(rsp("s").0, "S05");
+        assert_eq!(rsp("p5").0, "6001");
+        assert_eq!(rsp("Z2,c000,1").0, "OK");
+        assert_eq!(rsp("c").0, "T05watch:c000;");
+        assert_eq!(rsp("z2,c000,1").0, "OK");
+        // Into the unimplemented opcode
+        assert_eq!(rsp("c").0, "S04");
+        assert_eq!(rsp("?").0, "S04");
+        assert_eq!(rsp("p5").0, "5401");
+        assert_eq!(rsp("D"), ("OK".to_string(), GdbStatus::Detached));
+    }
+
+    #[test]
+    fn ctrl_c_interrupts_a_running_machine() {
+        let (mut gb, mut stub, mut client) = attach();
+        let (answer, _) = exchange("Mc100,2:18fe", &mut gb, &mut stub, &mut client);
+        assert_eq!(answer, "OK");
+        // Continue at a JR -2 loop, which never stops on its own
+        client.write_all(&packet("cc100")).unwrap();
+        let status = stub.run_frame(&mut gb);
+        assert!(matches!(status, GdbStatus::Running { .. }));
+        client.write_all(&[0x03]).unwrap();
+        assert_eq!(stub.run_frame(&mut gb), GdbStatus::Paused);
+        assert_eq!(reply(&mut client), "S02");
+        assert_eq!(exchange("p5", &mut gb, &mut stub, &mut client).0, "00c1");
+    }
+}
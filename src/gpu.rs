@@ -1,5 +1,8 @@
+use crate::cgb;
 use crate::cpu::TimeIncrement;
-use crate::MemoryAccess;
+use crate::fifo::Fifo;
+use crate::memory::MemoryAccess;
+use crate::savestate::{StateError, StateReader, StateWriter};
 
 #[derive(Debug)]
 pub struct Gpu {
@@ -8,6 +11,38 @@ pub struct Gpu {
     line: u8,
     framebuffer: Framebuffer,
     window_line: u8,
+    // OR of all enabled STAT interrupt sources; the interrupt fires on its rising edge
+    stat_irq_line: bool,
+    // Pixel FIFO renderer, when selected instead of the scanline renderer
+    fifo: Option<Fifo>,
+    // Length of the current line's HBlank: what mode 3 left of the line
+    hblank_dots: usize,
+}
+
+// Dots per line after the 80-dot OAM scan, shared by modes 3 and 0
+const LINE_DOTS_AFTER_OAM: usize = 376;
+// Mode 3 length assumed by the scanline renderer
+const SCANLINE_MODE3_DOTS: usize = 172;
+
+/// Which PPU implementation draws mode 3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    /// Whole lines at the end of mode 3 with registers sampled once. Fast.
+    Scanline,
+    /// Pixel FIFOs and fetcher, dot by dot: mid-line register writes and
+    /// variable mode 3 length.
+    Fifo,
+}
+
+impl Renderer {
+    /// Look a renderer up by name: "scanline" or "fifo".
+    pub fn from_name(name: &str) -> Option<Renderer> {
+        match name {
+            "scanline" => Some(Renderer::Scanline),
+            "fifo" => Some(Renderer::Fifo),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Default, Debug, Copy, Clone)]
@@ -21,8 +56,25 @@ pub struct Rgba {
 #[derive(Debug, Clone)]
 pub struct Framebuffer(pub Vec<Rgba>);
 
+impl Framebuffer {
+    /// Pixels as packed RGB bytes, row-major.
+    pub fn rgb_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|p| [p.r, p.g, p.b]).collect()
+    }
+
+    /// 64-bit FNV-1a over `rgb_bytes`, for comparing screens against known-good output.
+    pub fn hash(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for byte in self.rgb_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        hash
+    }
+}
+
 // Game Boy monochrome palette: color IDs 0-3 → RGBA
-const PALETTE: [Rgba; 4] = [
+pub(crate) const PALETTE: [Rgba; 4] = [
     Rgba {
         r: 255,
         g: 255,
@@ -54,16 +106,28 @@ fn gen_framebuffer() -> Framebuffer {
 }
 
 /// Decode a palette byte (BGP/OBP0/OBP1) for a given color_id.
-fn decode_palette(palette: u8, color_id: u8) -> Rgba {
+pub(crate) fn decode_palette(palette: u8, color_id: u8) -> Rgba {
     let shade = (palette >> (color_id * 2)) & 0x3;
     PALETTE[shade as usize]
 }
 
-/// Read tile pixel color from VRAM at the given tile data address + row.
-fn tile_pixel(memory: &Box<dyn MemoryAccess>, tile_data_addr: u16, pixel_x: u8, pixel_y: u8) -> u8 {
+/// Convert a CGB BGR555 colour for the framebuffer.
+pub(crate) fn cgb_rgba(color: u16) -> Rgba {
+    let (r, g, b) = cgb::rgb888(color);
+    Rgba { r, g, b, a: 255 }
+}
+
+/// Read tile pixel color from VRAM `bank` at the given tile data address + row.
+fn tile_pixel(
+    memory: &dyn MemoryAccess,
+    bank: usize,
+    tile_data_addr: u16,
+    pixel_x: u8,
+    pixel_y: u8,
+) -> u8 {
     let row_addr = tile_data_addr.wrapping_add((pixel_y as u16) * 2);
-    let byte1 = memory.read_byte(row_addr);
-    let byte2 = memory.read_byte(row_addr + 1);
+    let byte1 = memory.read_vram(bank, row_addr);
+    let byte2 = memory.read_vram(bank, row_addr + 1);
     let bit = 7 - pixel_x;
     let lo = (byte1 >> bit) & 1;
     let hi = (byte2 >> bit) & 1;
@@ -71,7 +135,7 @@ fn tile_pixel(memory: &Box<dyn MemoryAccess>, tile_data_addr: u16, pixel_x: u8,
 }
 
 /// Compute tile data address given tile index and LCDC bit 4.
-fn tile_data_addr(tile_index: u8, lcdc: u8) -> u16 {
+pub(crate) fn tile_data_addr(tile_index: u8, lcdc: u8) -> u16 {
     if lcdc & 0x10 != 0 {
         // Unsigned: 0x8000 + index * 16
         0x8000u16.wrapping_add((tile_index as u16) * 16)
@@ -82,8 +146,58 @@ fn tile_data_addr(tile_index: u8, lcdc: u8) -> u16 {
     }
 }
 
+/// Color ID of one pixel of the BG/window tile whose tile map entry is at
+/// `map_addr`, and the entry's CGB attributes (0 on DMG): palette in bits 0-2,
+/// tile VRAM bank in bit 3, X/Y flip in bits 5-6, priority over objects in bit 7.
+fn map_pixel(
+    memory: &dyn MemoryAccess,
+    cgb: bool,
+    map_addr: u16,
+    lcdc: u8,
+    tile_px: u8,
+    tile_py: u8,
+) -> (u8, u8) {
+    let tile_idx = memory.read_vram(0, map_addr);
+    let attr = if cgb {
+        memory.read_vram(1, map_addr)
+    } else {
+        0
+    };
+    let tile_px = if attr & 0x20 != 0 {
+        7 - tile_px
+    } else {
+        tile_px
+    };
+    let tile_py = if attr & 0x40 != 0 {
+        7 - tile_py
+    } else {
+        tile_py
+    };
+    let bank = ((attr >> 3) & 1) as usize;
+    let color_id = tile_pixel(
+        memory,
+        bank,
+        tile_data_addr(tile_idx, lcdc),
+        tile_px,
+        tile_py,
+    );
+    (color_id, attr)
+}
+
+/// Screen colour of a BG/window pixel: through BGP on DMG, or the palette
+/// named by the tile's attributes on CGB.
+fn bg_color(memory: &dyn MemoryAccess, cgb: bool, bgp: u8, attr: u8, color_id: u8) -> Rgba {
+    if cgb {
+        cgb_rgba(memory.cgb_color(false, attr & 0x07, color_id))
+    } else {
+        decode_palette(bgp, color_id)
+    }
+}
+
 fn render_scan(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>) {
+    let memory: &dyn MemoryAccess = &**memory;
     let lcdc = memory.read_byte(0xFF40);
+    let cgb = memory.cgb_mode();
 
     let scroll_x = memory.read_byte(0xFF43);
     let scroll_y = memory.read_byte(0xFF42);
@@ -96,11 +210,15 @@ fn render_scan(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>) {
 
     let line_start = line as usize * 160;
 
-    // Track which pixels have non-transparent BG (for sprite priority)
+    // Track which pixels have non-transparent BG, and on CGB which BG tiles
+    // claim priority over objects (for sprite priority)
     let mut bg_opaque = [false; 160];
+    let mut bg_priority = [false; 160];
 
     // ── Background ──────────────────────────────────────────────────────────
-    if lcdc & 0x01 != 0 {
+    // On CGB, LCDC bit 0 does not hide the background; it only takes away
+    // its priority over objects (see the sprite pass)
+    if lcdc & 0x01 != 0 || cgb {
         let bg_map_base: u16 = if lcdc & 0x08 != 0 { 0x9C00 } else { 0x9800 };
 
         for pixel_x in 0u8..160 {
@@ -112,12 +230,12 @@ fn render_scan(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>) {
             let tile_px = bg_x % 8;
             let tile_py = bg_y % 8;
 
-            let tile_idx =
-                memory.read_byte(bg_map_base + (tile_row as u16) * 32 + (tile_col as u16));
-            let addr = tile_data_addr(tile_idx, lcdc);
-            let color_id = tile_pixel(memory, addr, tile_px, tile_py);
+            let map_addr = bg_map_base + (tile_row as u16) * 32 + (tile_col as u16);
+            let (color_id, attr) = map_pixel(memory, cgb, map_addr, lcdc, tile_px, tile_py);
             bg_opaque[pixel_x as usize] = color_id != 0;
-            gpu.framebuffer.0[line_start + pixel_x as usize] = decode_palette(bgp, color_id);
+            bg_priority[pixel_x as usize] = attr & 0x80 != 0;
+            gpu.framebuffer.0[line_start + pixel_x as usize] =
+                bg_color(memory, cgb, bgp, attr, color_id);
         }
     } else {
         // BG off: fill white
@@ -145,12 +263,12 @@ fn render_scan(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>) {
             let tile_col = (win_px_abs / 8) as u8;
             let tile_px = (win_px_abs % 8) as u8;
 
-            let tile_idx =
-                memory.read_byte(win_map_base + (tile_row as u16) * 32 + (tile_col as u16));
-            let addr = tile_data_addr(tile_idx, lcdc);
-            let color_id = tile_pixel(memory, addr, tile_px, tile_py);
+            let map_addr = win_map_base + (tile_row as u16) * 32 + (tile_col as u16);
+            let (color_id, attr) = map_pixel(memory, cgb, map_addr, lcdc, tile_px, tile_py);
             bg_opaque[pixel_x as usize] = color_id != 0;
-            gpu.framebuffer.0[line_start + pixel_x as usize] = decode_palette(bgp, color_id);
+            bg_priority[pixel_x as usize] = attr & 0x80 != 0;
+            gpu.framebuffer.0[line_start + pixel_x as usize] =
+                bg_color(memory, cgb, bgp, attr, color_id);
         }
         gpu.window_line += 1;
     }
@@ -178,14 +296,19 @@ fn render_scan(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>) {
                 } // max 10 sprites per line
             }
         }
-        // Sort by X coordinate (lower X draws last = highest priority)
-        visible.sort_by_key(|s| s.2);
+        // DMG: sort by X coordinate (lower X draws last = highest priority).
+        // CGB: OAM order alone decides.
+        if !cgb {
+            visible.sort_by_key(|s| s.2);
+        }
 
         for (_, sy, sx, tile_idx, attr) in visible.iter().rev() {
             let flip_x = attr & 0x20 != 0;
             let flip_y = attr & 0x40 != 0;
             let behind_bg = attr & 0x80 != 0;
             let palette = if attr & 0x10 != 0 { obp1 } else { obp0 };
+            // CGB: tile VRAM bank in bit 3
+            let bank = if cgb { ((attr >> 3) & 1) as usize } else { 0 };
 
             // For 8x16 sprites, mask the lowest bit of tile index
             let tile = if sprite_height == 16 {
@@ -214,18 +337,30 @@ fn render_scan(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>) {
                     continue;
                 }
                 let tile_px = if flip_x { 7 - px } else { px };
-                let color_id = tile_pixel(memory, tile_addr, tile_px, tile_row);
+                let color_id = tile_pixel(memory, bank, tile_addr, tile_px, tile_row);
                 if color_id == 0 {
                     continue;
                 } // transparent
 
-                // Sprite behind BG: only draw if BG is transparent (color 0)
-                if behind_bg && bg_opaque[screen_x as usize] {
+                // Sprite behind BG: only draw if BG is transparent (color 0).
+                // On CGB the BG tile can also claim priority, and LCDC bit 0
+                // clear puts every sprite in front.
+                let x = screen_x as usize;
+                let bg_wins = if cgb {
+                    lcdc & 0x01 != 0 && bg_opaque[x] && (behind_bg || bg_priority[x])
+                } else {
+                    behind_bg && bg_opaque[x]
+                };
+                if bg_wins {
                     continue;
                 }
 
-                let idx = line_start + screen_x as usize;
-                gpu.framebuffer.0[idx] = decode_palette(palette, color_id);
+                gpu.framebuffer.0[line_start + x] = if cgb {
+                    // CGB: palette number in bits 0-2
+                    cgb_rgba(memory.cgb_color(true, attr & 0x07, color_id))
+                } else {
+                    decode_palette(palette, color_id)
+                };
             }
         }
     }
@@ -247,16 +382,31 @@ fn step_mode(
             None
         }
         ScanMode::AccessVram => {
-            if gpu.mode_clock >= 172 {
-                gpu.mode_clock -= 172;
+            if let Some(fifo) = gpu.fifo.as_mut() {
+                while gpu.mode_clock > 0 {
+                    gpu.mode_clock -= 1;
+                    if fifo.dot(&**memory, &mut gpu.framebuffer, gpu.line, gpu.window_line) {
+                        if fifo.window_drawn() {
+                            gpu.window_line += 1;
+                        }
+                        gpu.hblank_dots = LINE_DOTS_AFTER_OAM - fifo.mode3_dots() as usize;
+                        gpu.scan_mode = ScanMode::HorizontalBlank;
+                        memory.hblank();
+                        break;
+                    }
+                }
+            } else if gpu.mode_clock >= SCANLINE_MODE3_DOTS {
+                gpu.mode_clock -= SCANLINE_MODE3_DOTS;
+                gpu.hblank_dots = LINE_DOTS_AFTER_OAM - SCANLINE_MODE3_DOTS;
                 gpu.scan_mode = ScanMode::HorizontalBlank;
                 render_scan(gpu, memory);
+                memory.hblank();
             }
             None
         }
         ScanMode::HorizontalBlank => {
-            if gpu.mode_clock >= 204 {
-                gpu.mode_clock -= 204;
+            if gpu.mode_clock >= gpu.hblank_dots {
+                gpu.mode_clock -= gpu.hblank_dots;
                 gpu.line += 1;
 
                 if gpu.line == 144 {
@@ -296,9 +446,21 @@ impl Gpu {
             line: 0,
             framebuffer: gen_framebuffer(),
             window_line: 0,
+            stat_irq_line: false,
+            fifo: None,
+            hblank_dots: LINE_DOTS_AFTER_OAM - SCANLINE_MODE3_DOTS,
         }
     }
 
+    /// Switch PPU implementation. Takes effect from the next dot; a line in
+    /// progress is restarted by the FIFO renderer.
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.fifo = match renderer {
+            Renderer::Scanline => None,
+            Renderer::Fifo => Some(Fifo::new()),
+        };
+    }
+
     pub fn step(
         &mut self,
         time_increment: TimeIncrement,
@@ -312,13 +474,87 @@ impl Gpu {
             self.mode_clock = 0;
             self.window_line = 0;
             self.scan_mode = ScanMode::HorizontalBlank;
+            self.stat_irq_line = false;
             memory.write_byte(0xFF44, 0);
+            let lyc = memory.read_byte(0xFF45);
+            memory.update_stat(0, lyc == 0);
             return None;
         }
         // Write LY so CPU can read it; do NOT write scroll/palette registers back
         // (the CPU/game writes those, we only read them)
         memory.write_byte(0xFF44, self.line);
-        step_mode(self, memory, time_increment)
+        let frame = step_mode(self, memory, time_increment);
+        memory.write_byte(0xFF44, self.line);
+        self.update_stat(memory);
+        frame
+    }
+
+    /// The frame being drawn; complete whenever `step` has just returned one.
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.scan_mode.stat_bits());
+        w.u32(self.mode_clock as u32);
+        w.u8(self.line);
+        w.u8(self.window_line);
+        w.bool(self.stat_irq_line);
+        w.bytes(&self.framebuffer.rgb_bytes());
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.scan_mode = match r.u8()? {
+            0 => ScanMode::HorizontalBlank,
+            1 => ScanMode::VerticalBlank,
+            2 => ScanMode::AccessOam,
+            3 => ScanMode::AccessVram,
+            _ => return Err(StateError::Invalid("PPU mode")),
+        };
+        self.mode_clock = r.u32()? as usize;
+        self.line = r.u8()?;
+        self.window_line = r.u8()?;
+        self.stat_irq_line = r.bool()?;
+        // Renderer state is not saved: the FIFO restarts the line, and HBlank
+        // takes the scanline renderer's length
+        self.hblank_dots = LINE_DOTS_AFTER_OAM - SCANLINE_MODE3_DOTS;
+        if self.fifo.is_some() {
+            self.fifo = Some(Fifo::new());
+        }
+        let pixels = r.bytes()?;
+        if pixels.len() != self.framebuffer.0.len() * 3 {
+            return Err(StateError::Invalid("framebuffer size"));
+        }
+        for (pixel, rgb) in self.framebuffer.0.iter_mut().zip(pixels.chunks_exact(3)) {
+            *pixel = Rgba {
+                r: rgb[0],
+                g: rgb[1],
+                b: rgb[2],
+                a: 255,
+            };
+        }
+        Ok(())
+    }
+
+    /// Mirror the current mode and LYC=LY into STAT and raise the STAT
+    /// interrupt (IF bit 1) on a rising edge of the combined interrupt line.
+    /// Because the sources are ORed, a source that becomes active while another
+    /// is still holding the line high does not fire again ("STAT IRQ blocking").
+    fn update_stat(&mut self, memory: &mut Box<dyn MemoryAccess>) {
+        let mode = self.scan_mode.stat_bits();
+        let coincidence = self.line == memory.read_byte(0xFF45);
+        memory.update_stat(mode, coincidence);
+
+        let stat = memory.read_byte(0xFF41);
+        let line = (stat & 0x08 != 0 && mode == 0)
+            || (stat & 0x10 != 0 && mode == 1)
+            || (stat & 0x20 != 0 && mode == 2)
+            || (stat & 0x40 != 0 && coincidence);
+        if line && !self.stat_irq_line {
+            let if_val = memory.read_byte(0xFF0F);
+            memory.write_byte(0xFF0F, if_val | 0x02);
+        }
+        self.stat_irq_line = line;
     }
 }
 
@@ -329,3 +565,118 @@ enum ScanMode {
     HorizontalBlank,
     VerticalBlank,
 }
+
+impl ScanMode {
+    /// Mode number as reported in STAT bits 0-1.
+    fn stat_bits(&self) -> u8 {
+        match self {
+            ScanMode::HorizontalBlank => 0,
+            ScanMode::VerticalBlank => 1,
+            ScanMode::AccessOam => 2,
+            ScanMode::AccessVram => 3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Memory;
+
+    /// A PPU at the start of line 0's HBlank with the LCD on and STAT
+    /// interrupts enabled as `stat`.
+    fn lcd_on(stat: u8, lyc: u8) -> (Gpu, Box<dyn MemoryAccess>) {
+        let mut memory: Box<dyn MemoryAccess> =
+            Box::new(Memory::initialize_with_rom(vec![0; 0x8000]).unwrap());
+        memory.write_byte(0xFF40, 0x91);
+        memory.write_byte(0xFF41, stat);
+        memory.write_byte(0xFF45, lyc);
+        memory.write_byte(0xFF0F, 0x00);
+        (Gpu::initialize(), memory)
+    }
+
+    /// Run the PPU for `dots` dots, one M-cycle at a time.
+    fn run(gpu: &mut Gpu, memory: &mut Box<dyn MemoryAccess>, dots: usize) {
+        for _ in 0..dots / 4 {
+            gpu.step(TimeIncrement { m: 1, t: 4 }, memory);
+        }
+    }
+
+    /// Whether the STAT interrupt has been requested, clearing the request.
+    fn take_stat_irq(memory: &mut Box<dyn MemoryAccess>) -> bool {
+        let requested = memory.read_byte(0xFF0F) & 0x02 != 0;
+        memory.write_byte(0xFF0F, 0x00);
+        requested
+    }
+
+    #[test]
+    fn stat_mode_follows_oam_scan_drawing_hblank_and_vblank() {
+        let (mut gpu, mut memory) = lcd_on(0x00, 0xFF);
+        let mode = |memory: &dyn MemoryAccess| memory.read_byte(0xFF41) & 0x03;
+        run(&mut gpu, &mut memory, 204);
+        assert_eq!((memory.read_byte(0xFF44), mode(&*memory)), (1, 2));
+        run(&mut gpu, &mut memory, 80);
+        assert_eq!(mode(&*memory), 3);
+        run(&mut gpu, &mut memory, 172);
+        assert_eq!(mode(&*memory), 0);
+        run(&mut gpu, &mut memory, 204);
+        assert_eq!((memory.read_byte(0xFF44), mode(&*memory)), (2, 2));
+
+        run(&mut gpu, &mut memory, 142 * 456);
+        assert_eq!((memory.read_byte(0xFF44), mode(&*memory)), (144, 1));
+        run(&mut gpu, &mut memory, 10 * 456);
+        assert_eq!((memory.read_byte(0xFF44), mode(&*memory)), (0, 2));
+    }
+
+    #[test]
+    fn coincidence_flag_and_interrupt_follow_lyc() {
+        let (mut gpu, mut memory) = lcd_on(0x40, 3);
+        run(&mut gpu, &mut memory, 204 + 456);
+        assert_eq!(memory.read_byte(0xFF44), 2);
+        assert_eq!(memory.read_byte(0xFF41) & 0x04, 0);
+        assert!(!take_stat_irq(&mut memory));
+
+        run(&mut gpu, &mut memory, 456);
+        assert_eq!(memory.read_byte(0xFF44), 3);
+        assert_eq!(memory.read_byte(0xFF41) & 0x04, 0x04);
+        assert!(take_stat_irq(&mut memory));
+
+        run(&mut gpu, &mut memory, 456);
+        assert_eq!(memory.read_byte(0xFF41) & 0x04, 0);
+        assert!(!take_stat_irq(&mut memory));
+    }
+
+    #[test]
+    fn lyc_match_during_hblank_interrupt_does_not_fire_again() {
+        // Line 1's HBlank raises the line; LY=LYC on line 2 takes over from it
+        let (mut gpu, mut memory) = lcd_on(0x48, 2);
+        run(&mut gpu, &mut memory, 204 + 252);
+        assert_eq!(memory.read_byte(0xFF41) & 0x03, 0);
+        assert!(take_stat_irq(&mut memory));
+        run(&mut gpu, &mut memory, 456);
+        assert_eq!(memory.read_byte(0xFF44), 2);
+        assert!(!take_stat_irq(&mut memory));
+
+        // Line 3's OAM scan drops the line, so its HBlank fires again
+        run(&mut gpu, &mut memory, 456);
+        assert!(take_stat_irq(&mut memory));
+
+        // Without the HBlank source, the same LY=LYC does fire
+        let (mut gpu, mut memory) = lcd_on(0x40, 2);
+        run(&mut gpu, &mut memory, 204 + 456);
+        assert!(take_stat_irq(&mut memory));
+    }
+
+    #[test]
+    fn stat_reads_mode_0_with_the_lcd_off() {
+        let (mut gpu, mut memory) = lcd_on(0x00, 0xFF);
+        run(&mut gpu, &mut memory, 204 + 80);
+        assert_eq!(memory.read_byte(0xFF41) & 0x03, 3);
+        memory.write_byte(0xFF40, 0x11);
+        run(&mut gpu, &mut memory, 4);
+        assert_eq!(memory.read_byte(0xFF41) & 0x03, 0);
+        assert_eq!(memory.read_byte(0xFF44), 0);
+        run(&mut gpu, &mut memory, 456);
+        assert_eq!(memory.read_byte(0xFF41) & 0x03, 0);
+    }
+}
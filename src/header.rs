@@ -0,0 +1,293 @@
+// Cartridge header — the metadata block at 0x0100–0x014F of every ROM.
+//
+//   0x0134–0x0143  title (0x013F–0x0142 manufacturer code, 0x0143 CGB flag on newer carts)
+//   0x0144–0x0145  new licensee code
+//   0x0146         SGB flag
+//   0x0147         cartridge type (mapper and extras)
+//   0x0148–0x0149  ROM and RAM size codes
+//   0x014B         old licensee code (0x33 = use the new one)
+//   0x014C         version
+//   0x014D         header checksum, verified by the boot ROM
+//   0x014E–0x014F  global checksum (big-endian), not checked by hardware
+//
+// Parsing rejects files that cannot be a Game Boy ROM: too short to hold a
+// header, unknown size codes, or fewer bytes than the header declares.
+// Checksum mismatches are only reported, since plenty of homebrew ships with
+// a wrong global checksum.
+
+use crate::cartridge::CartridgeError;
+use std::fmt;
+
+const HEADER_END: usize = 0x0150;
+const TITLE: std::ops::Range<usize> = 0x0134..0x0144;
+const MANUFACTURER: std::ops::Range<usize> = 0x013F..0x0143;
+const CGB_FLAG: usize = 0x0143;
+const NEW_LICENSEE: usize = 0x0144;
+const SGB_FLAG: usize = 0x0146;
+const CART_TYPE: usize = 0x0147;
+const ROM_SIZE: usize = 0x0148;
+const RAM_SIZE: usize = 0x0149;
+const OLD_LICENSEE: usize = 0x014B;
+const VERSION: usize = 0x014C;
+const HEADER_CHECKSUM: usize = 0x014D;
+const GLOBAL_CHECKSUM: usize = 0x014E;
+
+/// What the CGB flag at 0x0143 says about Game Boy Color support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgbSupport {
+    /// Plain DMG cartridge.
+    None,
+    /// Runs on DMG, with CGB enhancements (0x80).
+    Enhanced,
+    /// Refuses to run on anything but a CGB (0xC0).
+    Only,
+}
+
+/// Publisher code: one byte at 0x014B, or two ASCII characters at 0x0144
+/// when the old code is 0x33.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Licensee {
+    Old(u8),
+    New([u8; 2]),
+}
+
+impl fmt::Display for Licensee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Licensee::Old(code) => write!(f, "{:02X}", code),
+            Licensee::New(code) => write!(f, "{}", String::from_utf8_lossy(code)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Title in printable ASCII, without padding.
+    pub title: String,
+    /// Four-character manufacturer code, present on some CGB-era cartridges.
+    pub manufacturer: Option<String>,
+    pub cgb: CgbSupport,
+    /// Whether the cartridge supports Super Game Boy functions.
+    pub sgb: bool,
+    /// Cartridge type byte: mapper plus RAM, battery, timer and rumble.
+    pub cartridge_type: u8,
+    /// ROM size in bytes.
+    pub rom_size: usize,
+    /// External RAM size in bytes (MBC2's built-in RAM is not counted).
+    pub ram_size: usize,
+    pub licensee: Licensee,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    /// Whether `header_checksum` matches the header; the boot ROM locks up if not.
+    pub header_checksum_ok: bool,
+    /// Whether `global_checksum` matches the sum of every other ROM byte.
+    pub global_checksum_ok: bool,
+}
+
+impl Header {
+    /// Parse and validate the header of `rom`.
+    pub fn parse(rom: &[u8]) -> Result<Header, CartridgeError> {
+        if rom.len() < HEADER_END {
+            return Err(CartridgeError::TooSmall(rom.len()));
+        }
+        let rom_size = rom_size_from_code(rom[ROM_SIZE])
+            .ok_or(CartridgeError::InvalidRomSize(rom[ROM_SIZE]))?;
+        let ram_size = ram_size_from_code(rom[RAM_SIZE])
+            .ok_or(CartridgeError::InvalidRamSize(rom[RAM_SIZE]))?;
+        if rom.len() < rom_size {
+            return Err(CartridgeError::Truncated {
+                expected: rom_size,
+                actual: rom.len(),
+            });
+        }
+
+        let cgb = match rom[CGB_FLAG] {
+            0xC0 => CgbSupport::Only,
+            flag if flag & 0x80 != 0 => CgbSupport::Enhanced,
+            _ => CgbSupport::None,
+        };
+        // On CGB-era carts the last title bytes may hold a manufacturer code
+        // and the CGB flag; older carts use all 16 bytes for the title.
+        let mut title_bytes = &rom[TITLE];
+        let mut manufacturer = None;
+        if cgb != CgbSupport::None {
+            let code = &rom[MANUFACTURER];
+            if code
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+            {
+                manufacturer = Some(String::from_utf8_lossy(code).into_owned());
+                title_bytes = &rom[TITLE.start..MANUFACTURER.start];
+            } else {
+                title_bytes = &rom[TITLE.start..CGB_FLAG];
+            }
+        }
+        let title = title_bytes
+            .iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '?'
+                }
+            })
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+
+        let licensee = match rom[OLD_LICENSEE] {
+            0x33 => Licensee::New([rom[NEW_LICENSEE], rom[NEW_LICENSEE + 1]]),
+            code => Licensee::Old(code),
+        };
+        let header_checksum = rom[HEADER_CHECKSUM];
+        let global_checksum = u16::from_be_bytes([rom[GLOBAL_CHECKSUM], rom[GLOBAL_CHECKSUM + 1]]);
+
+        Ok(Header {
+            title,
+            manufacturer,
+            cgb,
+            sgb: rom[SGB_FLAG] == 0x03,
+            cartridge_type: rom[CART_TYPE],
+            rom_size,
+            ram_size,
+            licensee,
+            version: rom[VERSION],
+            header_checksum,
+            global_checksum,
+            header_checksum_ok: compute_header_checksum(rom) == header_checksum,
+            global_checksum_ok: compute_global_checksum(rom) == global_checksum,
+        })
+    }
+
+    /// Whether the cartridge keeps its RAM (and clock) alive with a battery.
+    pub fn has_battery(&self) -> bool {
+        matches!(
+            self.cartridge_type,
+            0x03 | 0x06 | 0x09 | 0x0D | 0x0F | 0x10 | 0x13 | 0x1B | 0x1E
+        )
+    }
+}
+
+/// ROM size in bytes for header byte 0x0148: 32KB << code, plus the three
+/// odd sizes listed in some documentation.
+fn rom_size_from_code(code: u8) -> Option<usize> {
+    match code {
+        0x00..=0x08 => Some(0x8000 << code),
+        0x52 => Some(72 * 0x4000),
+        0x53 => Some(80 * 0x4000),
+        0x54 => Some(96 * 0x4000),
+        _ => None,
+    }
+}
+
+/// External RAM size in bytes for header byte 0x0149.
+fn ram_size_from_code(code: u8) -> Option<usize> {
+    match code {
+        0x00 => Some(0),
+        // Unused; some homebrew declares it for 2KB
+        0x01 => Some(0x800),
+        0x02 => Some(0x2000),
+        0x03 => Some(0x8000),
+        0x04 => Some(0x20000),
+        0x05 => Some(0x10000),
+        _ => None,
+    }
+}
+
+/// The boot ROM's check over 0x0134–0x014C.
+fn compute_header_checksum(rom: &[u8]) -> u8 {
+    rom[TITLE.start..HEADER_CHECKSUM]
+        .iter()
+        .fold(0u8, |sum, &b| sum.wrapping_sub(b).wrapping_sub(1))
+}
+
+/// Sum of every ROM byte except the global checksum itself.
+fn compute_global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|&(addr, _)| addr != GLOBAL_CHECKSUM && addr != GLOBAL_CHECKSUM + 1)
+        .fold(0u16, |sum, (_, &b)| sum.wrapping_add(b as u16))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Memory;
+
+    /// A 32KB ROM-only image with no RAM.
+    fn rom() -> Vec<u8> {
+        vec![0; 0x8000]
+    }
+
+    #[test]
+    fn valid_header_parses() {
+        let mut rom = rom();
+        rom[TITLE][..4].copy_from_slice(b"PONG");
+        rom[HEADER_CHECKSUM] = compute_header_checksum(&rom);
+        let header = Header::parse(&rom).unwrap();
+        assert_eq!(header.title, "PONG");
+        assert_eq!(header.rom_size, 0x8000);
+        assert_eq!(header.ram_size, 0);
+        assert!(header.header_checksum_ok);
+    }
+
+    #[test]
+    fn file_that_ends_inside_the_header_is_too_small() {
+        let rom = vec![0; HEADER_END - 1];
+        assert_eq!(
+            Header::parse(&rom),
+            Err(CartridgeError::TooSmall(HEADER_END - 1))
+        );
+    }
+
+    #[test]
+    fn unknown_rom_size_code_is_rejected() {
+        let mut rom = rom();
+        rom[ROM_SIZE] = 0x09;
+        assert_eq!(
+            Header::parse(&rom),
+            Err(CartridgeError::InvalidRomSize(0x09))
+        );
+    }
+
+    #[test]
+    fn unknown_ram_size_code_is_rejected() {
+        let mut rom = rom();
+        rom[RAM_SIZE] = 0x06;
+        assert_eq!(
+            Header::parse(&rom),
+            Err(CartridgeError::InvalidRamSize(0x06))
+        );
+    }
+
+    #[test]
+    fn rom_shorter_than_its_declared_size_is_truncated() {
+        let mut rom = rom();
+        rom[ROM_SIZE] = 0x01;
+        assert_eq!(
+            Header::parse(&rom),
+            Err(CartridgeError::Truncated {
+                expected: 0x10000,
+                actual: 0x8000,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_mapper_is_unsupported() {
+        let mut rom = rom();
+        rom[CART_TYPE] = 0xFC;
+        assert_eq!(
+            Memory::initialize_with_rom(rom).err(),
+            Some(CartridgeError::UnsupportedType(0xFC))
+        );
+    }
+
+    #[test]
+    fn unreadable_file_is_an_io_error() {
+        let result = Memory::initialize("/nonexistent/rom.gb");
+        assert!(matches!(result.err(), Some(CartridgeError::Io(_))));
+    }
+}
@@ -0,0 +1,204 @@
+// Joypad — the P1/JOYP register (0xFF00).
+//
+// The eight buttons sit on a 2×4 matrix. Writing P1 bits 4 and 5 low selects
+// the D-pad row (P14) and the button row (P15) respectively; the low nibble
+// then reads the four output lines P10–P13, where 0 means a selected button
+// on that line is held. With both rows selected, a line is low if either of
+// its buttons is held.
+//
+// The joypad interrupt (IF bit 4) fires whenever one of P10–P13 goes from
+// high to low — a press on a selected row, or selecting a row on which a
+// button is already held. Games use it to wake from HALT or STOP.
+
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+impl Button {
+    /// Bit for this button in a held-buttons mask: the D-pad in bits 0-3 and
+    /// A, B, Select, Start in bits 4-7, each in P10-P13 line order.
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Look a button up by its lower-case name, e.g. "a" or "start".
+    pub fn from_name(name: &str) -> Option<Button> {
+        let button = match name {
+            "right" => Button::Right,
+            "left" => Button::Left,
+            "up" => Button::Up,
+            "down" => Button::Down,
+            "a" => Button::A,
+            "b" => Button::B,
+            "select" => Button::Select,
+            "start" => Button::Start,
+            _ => return None,
+        };
+        Some(button)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Joypad {
+    // Held buttons, active-high, laid out as in `Button::mask`
+    held: u8,
+    // P14/P15 as last written (bits 4-5; 0 selects the row)
+    select: u8,
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Joypad {
+            held: 0,
+            select: 0x30,
+        }
+    }
+
+    /// P1 as the CPU reads it: unused bits 6-7 set, the select bits, then
+    /// P10-P13 (0 = a selected button on that line is held).
+    pub fn read(&self) -> u8 {
+        0xC0 | self.select | self.lines()
+    }
+
+    /// Write the select bits. Returns true when the joypad interrupt fires.
+    pub fn write(&mut self, value: u8) -> bool {
+        let before = self.lines();
+        self.select = value & 0x30;
+        falling_edge(before, self.lines())
+    }
+
+    /// Press or release one button. Returns true when the joypad interrupt fires.
+    pub fn set_button(&mut self, button: Button, pressed: bool) -> bool {
+        let held = if pressed {
+            self.held | button.mask()
+        } else {
+            self.held & !button.mask()
+        };
+        self.set_held(held)
+    }
+
+    /// Buttons currently held, as a `Button::mask` bitmask.
+    pub fn held(&self) -> u8 {
+        self.held
+    }
+
+    /// Replace every button's state at once. Returns true when the joypad interrupt fires.
+    pub fn set_held(&mut self, held: u8) -> bool {
+        let before = self.lines();
+        self.held = held;
+        falling_edge(before, self.lines())
+    }
+
+    /// P10-P13 output lines, active-low.
+    fn lines(&self) -> u8 {
+        let mut low = 0;
+        if self.select & 0x10 == 0 {
+            low |= self.held & 0x0F;
+        }
+        if self.select & 0x20 == 0 {
+            low |= self.held >> 4;
+        }
+        !low & 0x0F
+    }
+
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.held);
+        w.u8(self.select);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.held = r.u8()?;
+        self.select = r.u8()? & 0x30;
+        Ok(())
+    }
+}
+
+/// Whether any line went from high (1) to low (0).
+fn falling_edge(before: u8, after: u8) -> bool {
+    before & !after != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn button_masks_follow_the_p1_lines() {
+        let masks = [
+            (Button::Right, 0x01),
+            (Button::Left, 0x02),
+            (Button::Up, 0x04),
+            (Button::Down, 0x08),
+            (Button::A, 0x10),
+            (Button::B, 0x20),
+            (Button::Select, 0x40),
+            (Button::Start, 0x80),
+        ];
+        for (button, mask) in masks {
+            assert_eq!(button.mask(), mask, "{:?}", button);
+        }
+    }
+
+    #[test]
+    fn each_row_reads_its_own_buttons() {
+        let mut joypad = Joypad::new();
+        joypad.set_held(Button::Right.mask() | Button::Start.mask());
+        joypad.write(0x20);
+        assert_eq!(joypad.read(), 0xEE);
+        joypad.write(0x10);
+        assert_eq!(joypad.read(), 0xD7);
+        joypad.write(0x30);
+        assert_eq!(joypad.read(), 0xFF);
+    }
+
+    #[test]
+    fn both_rows_selected_read_the_and_of_the_rows() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x00);
+        joypad.set_held(Button::Left.mask() | Button::Select.mask());
+        assert_eq!(joypad.read(), 0xC9);
+        joypad.set_held(Button::Up.mask() | Button::Select.mask());
+        assert_eq!(joypad.read(), 0xCB);
+    }
+
+    #[test]
+    fn interrupt_fires_only_when_a_selected_line_goes_low() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x20);
+        assert!(joypad.set_button(Button::Down, true));
+        // Already low: a second button on the same line does not fire
+        joypad.write(0x00);
+        assert!(!joypad.set_button(Button::Start, true));
+        assert!(!joypad.set_button(Button::Down, false));
+        assert!(!joypad.set_button(Button::Start, false));
+        assert!(joypad.set_button(Button::A, true));
+    }
+
+    #[test]
+    fn buttons_on_an_unselected_row_do_not_interrupt() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x20);
+        assert!(!joypad.set_button(Button::A, true));
+        assert_eq!(joypad.read(), 0xEF);
+        // Selecting the row the button is held on pulls its line low
+        assert!(joypad.write(0x10));
+        assert!(!joypad.write(0x10));
+        assert!(!joypad.write(0x30));
+    }
+}
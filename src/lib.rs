@@ -1,162 +1,30 @@
-// WASM frontend bindings — exports the emulator to JavaScript via wasm-bindgen.
-// All items here are used by the browser build target, not the native binary.
-#![allow(dead_code)]
-
-mod apu;
-mod cpu;
-mod gpu;
-mod memory;
-
-use std::panic;
-use wasm_bindgen::prelude::*;
-
-use cpu::Cpu;
-use gpu::Gpu;
-use memory::{Memory, MemoryAccess};
-use std::collections::VecDeque;
-
-const LOG_CAPACITY: usize = 64;
-
-// Game Boy screen dimensions
-const SCREEN_WIDTH: usize = 160;
-const SCREEN_HEIGHT: usize = 144;
-
-const TILESET_WIDTH: usize = 128; // 16 tiles across
-const TILESET_HEIGHT: usize = 192; // 24 tiles down
-
-const MEMORY_WIDTH: usize = 256;
-const MEMORY_HEIGHT: usize = 256;
-
-/// The main Emulator struct exposed to JavaScript.
-#[wasm_bindgen]
-pub struct Emulator {
-    cpu: Cpu,
-    gpu: Gpu,
-    memory: Box<dyn MemoryAccess>,
-    pixel_buffer: Vec<u8>,
-    // Joypad state: bit=0 means pressed (active-low)
-    joypad_buttons: u8,
-    joypad_dpad: u8,
-    // Instruction log: most-recent first, capped at LOG_CAPACITY entries
-    instruction_log: VecDeque<String>,
-    // Audio: stereo f32 samples accumulated during tick(), drained by JS each frame
-    audio_buf: Vec<f32>,
-}
-
-#[wasm_bindgen]
-impl Emulator {
-    /// Creates a new Emulator instance with the ROM bytes provided by JavaScript.
-    #[wasm_bindgen(constructor)]
-    pub fn new(rom_data: Vec<u8>) -> Self {
-        panic::set_hook(Box::new(console_error_panic_hook::hook));
-        let memory = Box::new(Memory::initialize_with_rom(rom_data)) as Box<dyn MemoryAccess>;
-        Emulator {
-            cpu: Cpu::initialize(),
-            gpu: Gpu::initialize(),
-            memory,
-            pixel_buffer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT * 4],
-            joypad_buttons: 0xFF,
-            joypad_dpad: 0xFF,
-            instruction_log: VecDeque::with_capacity(LOG_CAPACITY),
-            audio_buf: Vec::with_capacity(4096),
-        }
-    }
-
-    /// Executes enough CPU and GPU cycles to produce one frame.
-    /// Call this from a requestAnimationFrame loop in JavaScript.
-    pub fn tick(&mut self) {
-        loop {
-            let (time_increment, instr) = self.cpu.step(&mut self.memory);
-            // Accumulate APU samples (stereo f32, interleaved L/R)
-            if let Some((l, r)) = self.memory.tick_apu_sample(time_increment.t as u32) {
-                self.audio_buf.push(l as f32 / 32768.0);
-                self.audio_buf.push(r as f32 / 32768.0);
-            }
-            if self.instruction_log.len() == LOG_CAPACITY {
-                self.instruction_log.pop_back();
-            }
-            self.instruction_log.push_front(instr);
-            self.cpu.handle_interrupts(&mut self.memory);
-            if let Some(framebuffer) = self.gpu.step(time_increment, &mut self.memory) {
-                for (i, pixel) in framebuffer.0.iter().enumerate() {
-                    let offset = i * 4;
-                    self.pixel_buffer[offset] = pixel.r;
-                    self.pixel_buffer[offset + 1] = pixel.g;
-                    self.pixel_buffer[offset + 2] = pixel.b;
-                    self.pixel_buffer[offset + 3] = 255;
-                }
-                break;
-            }
-        }
-    }
-
-    /// Drains and returns accumulated stereo audio samples as f32 in [-1, 1].
-    /// Interleaved: [L0, R0, L1, R1, ...]. Call this once per frame after tick().
-    pub fn get_audio_samples(&mut self) -> Vec<f32> {
-        let mut out = Vec::new();
-        std::mem::swap(&mut out, &mut self.audio_buf);
-        out
-    }
-
-    /// Returns the current frame as an RGBA byte vector (160×144×4 bytes).
-    pub fn get_framebuffer(&self) -> Vec<u8> {
-        self.pixel_buffer.clone()
-    }
-
-    /// Returns the instruction log as a newline-separated string (most-recent first).
-    pub fn get_instruction_log(&self) -> String {
-        self.instruction_log
-            .iter()
-            .cloned()
-            .collect::<Vec<_>>()
-            .join("\n")
-    }
-
-    /// Returns the VRAM tileset as an RGBA byte vector (128×192 px, 384 tiles).
-    pub fn get_tileset(&self) -> Vec<u8> {
-        let mut buf = vec![0u8; TILESET_WIDTH * TILESET_HEIGHT * 4];
-        self.memory.generate_tileset_rgba(&mut buf);
-        buf
-    }
-
-    /// Returns the full 64KB memory map as an RGBA byte vector (256×256 px).
-    pub fn get_memory_map(&self) -> Vec<u8> {
-        let mut buf = vec![0u8; MEMORY_WIDTH * MEMORY_HEIGHT * 4];
-        self.memory.generate_memory_rgba(&mut buf);
-        buf
-    }
-
-    /// Called by JavaScript on keydown. key_code is the browser KeyboardEvent.code value.
-    pub fn key_down(&mut self, key_code: String) {
-        match key_code.as_str() {
-            "ArrowRight" => self.joypad_dpad &= !0x01,
-            "ArrowLeft" => self.joypad_dpad &= !0x02,
-            "ArrowUp" => self.joypad_dpad &= !0x04,
-            "ArrowDown" => self.joypad_dpad &= !0x08,
-            "KeyZ" => self.joypad_buttons &= !0x01,  // A
-            "KeyX" => self.joypad_buttons &= !0x02,  // B
-            "Enter" => self.joypad_buttons &= !0x08, // Start
-            "Backspace" | "ShiftLeft" => self.joypad_buttons &= !0x04, // Select
-            _ => return,
-        }
-        self.memory
-            .set_joypad(self.joypad_buttons, self.joypad_dpad);
-    }
-
-    /// Called by JavaScript on keyup.
-    pub fn key_up(&mut self, key_code: String) {
-        match key_code.as_str() {
-            "ArrowRight" => self.joypad_dpad |= 0x01,
-            "ArrowLeft" => self.joypad_dpad |= 0x02,
-            "ArrowUp" => self.joypad_dpad |= 0x04,
-            "ArrowDown" => self.joypad_dpad |= 0x08,
-            "KeyZ" => self.joypad_buttons |= 0x01,
-            "KeyX" => self.joypad_buttons |= 0x02,
-            "Enter" => self.joypad_buttons |= 0x08,
-            "Backspace" | "ShiftLeft" => self.joypad_buttons |= 0x04,
-            _ => return,
-        }
-        self.memory
-            .set_joypad(self.joypad_buttons, self.joypad_dpad);
-    }
-}
+// The emulator core. `GameBoy` (src/gameboy.rs) is the public API every
+// frontend drives: the SDL window in src/main.rs, the headless runner in
+// src/bin/headless, the wasm-bindgen exports in src/wasm.rs, the test ROM
+// harness and the benchmark. The hardware modules stay public for tools that
+// need to look inside.
+
+pub mod apu;
+pub mod boot;
+pub mod bus;
+pub mod cartridge;
+pub mod cgb;
+pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+pub mod dma;
+pub mod fifo;
+pub mod gameboy;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gdb;
+pub mod gpu;
+pub mod header;
+pub mod joypad;
+pub mod mbc3;
+pub mod memory;
+pub mod savestate;
+pub mod serial;
+pub mod timer;
+pub mod wasm;
+
+pub use gameboy::{GameBoy, GameBoyError, Step};
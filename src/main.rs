@@ -1,12 +1,10 @@
-mod apu;
-mod cpu;
-mod gpu;
-mod memory;
-
-use cpu::Cpu;
-use gpu::Gpu;
-use memory::Memory;
-use memory::MemoryAccess;
+use emulator::boot::{Boot, Model};
+use emulator::debugger::{self, Debugger};
+use emulator::gdb::{GdbStatus, GdbStub};
+use emulator::gpu::Renderer;
+use emulator::joypad::Button;
+use emulator::serial::{CaptureSink, Loopback, SerialDevice, TcpLink};
+use emulator::GameBoy;
 use sdl2::audio::{AudioCallback, AudioSpecDesired};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
@@ -14,8 +12,14 @@ use sdl2::render::{Canvas, Texture};
 use sdl2::surface::Surface;
 use sdl2::video::Window;
 use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
 
+// Frames between periodic flushes of battery-backed RAM (~5 seconds)
+const SAVE_FLUSH_FRAMES: u32 = 300;
+
 // ---------------------------------------------------------------------------
 // SDL2 audio callback — drains samples from the shared queue into the output.
 // ---------------------------------------------------------------------------
@@ -35,23 +39,143 @@ impl AudioCallback for SampleQueue {
     }
 }
 
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} <rom_path> [options]", program);
+    eprintln!("  e.g. cargo run -- roms/snake.rom");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --serial-stdout        print bytes sent over the serial port to stdout");
+    eprintln!("  --link-loopback        wire the serial output straight back to its input");
+    eprintln!(
+        "  --link-listen <addr>   wait for a link cable partner on <addr> (e.g. 127.0.0.1:8765)"
+    );
+    eprintln!("  --link-connect <addr>  connect the link cable to a partner listening on <addr>");
+    eprintln!("  --bios <path>          run this boot ROM first instead of starting at 0x0100");
+    eprintln!(
+        "  --model <model>        post-boot state without --bios: dmg, mgb or cgb (default: cgb"
+    );
+    eprintln!("                         for cartridges with CGB support, otherwise dmg)");
+    eprintln!("  --ppu <renderer>       scanline (default, fast) or fifo (dot-accurate)");
+    eprintln!("  --strict               log every VRAM/OAM access the PPU blocked, with its PC");
+    eprintln!("  --debug                start paused with a debugger console on stdin");
+    eprintln!("  --gdb <port>           wait for gdb on 127.0.0.1:<port> and start paused");
+}
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    let rom_path = if args.len() >= 2 {
-        args[1].clone()
-    } else {
-        eprintln!("Usage: {} <rom_path>", args[0]);
-        eprintln!("  e.g. cargo run -- roms/snake.rom");
+    let mut rom_path: Option<String> = None;
+    let mut serial_device: Option<Box<dyn SerialDevice>> = None;
+    let mut bios_path: Option<String> = None;
+    let mut model: Option<Model> = None;
+    let mut renderer = Renderer::Scanline;
+    let mut strict = false;
+    let mut debug = false;
+    let mut gdb_port: Option<u16> = None;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--serial-stdout" => serial_device = Some(Box::new(CaptureSink::new(true))),
+            "--link-loopback" => serial_device = Some(Box::new(Loopback)),
+            "--strict" => strict = true,
+            "--debug" => debug = true,
+            "--link-listen" | "--link-connect" => {
+                let Some(addr) = iter.next() else {
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                };
+                let link = if arg == "--link-listen" {
+                    println!("Waiting for link cable partner on {}...", addr);
+                    TcpLink::listen(addr.as_str())
+                } else {
+                    TcpLink::connect(addr.as_str())
+                };
+                match link {
+                    Ok(link) => serial_device = Some(Box::new(link)),
+                    Err(e) => {
+                        eprintln!("Link cable error on '{}': {}", addr, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--gdb" => {
+                let Some(port) = iter.next().and_then(|port| port.parse().ok()) else {
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                };
+                gdb_port = Some(port);
+            }
+            "--bios" | "--model" | "--ppu" => {
+                let Some(value) = iter.next() else {
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                };
+                if arg == "--bios" {
+                    bios_path = Some(value.clone());
+                } else if arg == "--ppu" {
+                    let Some(r) = Renderer::from_name(value) else {
+                        eprintln!("Unknown renderer '{}'", value);
+                        print_usage(&args[0]);
+                        std::process::exit(1);
+                    };
+                    renderer = r;
+                } else if let Some(m) = Model::from_name(value) {
+                    model = Some(m);
+                } else {
+                    eprintln!("Unknown model '{}'", value);
+                    print_usage(&args[0]);
+                    std::process::exit(1);
+                }
+            }
+            _ => rom_path = Some(arg.clone()),
+        }
+    }
+    let Some(rom_path) = rom_path else {
+        print_usage(&args[0]);
         std::process::exit(1);
     };
 
+    let rom = match std::fs::read(&rom_path) {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("Failed to load ROM '{}': {}", rom_path, e);
+            std::process::exit(1);
+        }
+    };
+    let boot = match bios_path {
+        Some(path) => match std::fs::read(&path) {
+            Ok(bios) => Some(Boot::Bios(bios)),
+            Err(e) => {
+                eprintln!("Failed to read boot ROM '{}': {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => model.map(Boot::Skip),
+    };
+    let mut gb = match GameBoy::new(rom, boot) {
+        Ok(gb) => gb,
+        Err(e) => {
+            eprintln!("Failed to start '{}': {}", rom_path, e);
+            std::process::exit(1);
+        }
+    };
+    let header = gb.header();
+    if !header.header_checksum_ok {
+        eprintln!("Warning: header checksum mismatch; a real Game Boy would not boot this ROM");
+    }
+    let title = if header.title.is_empty() {
+        "emulator".to_string()
+    } else {
+        header.title.clone()
+    };
+
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
     let audio_subsystem = sdl_context.audio().unwrap();
 
     let scalar = 3u32;
     let window = video_subsystem
-        .window("emulator", 160 * scalar, 144 * scalar)
+        .window(&title, 160 * scalar, 144 * scalar)
         .build()
         .unwrap();
 
@@ -75,12 +199,49 @@ fn main() {
 
     audio_device.resume();
 
-    let mut memory = Box::new(Memory::initialize(&rom_path)) as Box<dyn MemoryAccess>;
-    let mut gpu = Gpu::initialize();
-    let mut cpu = Cpu::initialize();
+    if let Some(device) = serial_device {
+        gb.set_serial_device(device);
+    }
+    gb.set_renderer(renderer);
+    gb.set_strict(strict);
+
+    // Debugger console: commands typed on stdin, starting paused at the entry point
+    let mut console = debug.then(|| {
+        let mut debugger = Debugger::new();
+        debugger.pause();
+        println!("Debugger paused at the entry point; 'help' lists commands");
+        println!("{}", debugger::registers(&gb));
+        prompt();
+        (debugger, spawn_console())
+    });
+
+    // GDB remote stub: gdb drives the machine over TCP, starting paused
+    let mut gdb = gdb_port.map(|port| {
+        let addr = ("127.0.0.1", port);
+        println!("Waiting for gdb on 127.0.0.1:{}...", port);
+        match GdbStub::listen(addr) {
+            Ok(stub) => stub,
+            Err(e) => {
+                eprintln!("gdb stub error on port {}: {}", port, e);
+                std::process::exit(1);
+            }
+        }
+    });
+
+    // Battery-backed cartridge RAM lives next to the ROM as <rom>.sav
+    let save_path = Path::new(&rom_path).with_extension("sav");
+    if gb.save_data().is_some() {
+        match std::fs::read(&save_path) {
+            Ok(data) => gb.load_save_data(&data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("Failed to read save '{}': {}", save_path.display(), e),
+        }
+    }
+    let mut last_saved = gb.save_data();
+    let mut frames_since_flush = 0u32;
 
-    let mut joypad_buttons: u8 = 0xFF;
-    let mut joypad_dpad: u8 = 0xFF;
+    // Save state slot picked with the number keys; F5 saves and F8 loads it
+    let mut state_slot: u8 = 1;
 
     let mut event_pump = sdl_context.event_pump().unwrap();
 
@@ -100,59 +261,81 @@ fn main() {
                 } => {
                     break 'running;
                 }
+                Event::KeyDown {
+                    keycode: Some(key),
+                    repeat: false,
+                    ..
+                } if state_slot_key(key).is_some() => {
+                    state_slot = state_slot_key(key).unwrap();
+                    println!("Save state slot {}", state_slot);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F5),
+                    repeat: false,
+                    ..
+                } => {
+                    let path = state_path(&rom_path, state_slot);
+                    match std::fs::write(&path, gb.save_state()) {
+                        Ok(()) => println!("Saved state to slot {}", state_slot),
+                        Err(e) => eprintln!("Failed to write state '{}': {}", path.display(), e),
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F8),
+                    repeat: false,
+                    ..
+                } => {
+                    let path = state_path(&rom_path, state_slot);
+                    let result = std::fs::read(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|data| gb.load_state(&data).map_err(|e| e.to_string()));
+                    match result {
+                        Ok(()) => println!("Loaded state from slot {}", state_slot),
+                        Err(e) => eprintln!("Failed to load state '{}': {}", path.display(), e),
+                    }
+                }
                 Event::KeyDown {
                     keycode: Some(key), ..
                 } => {
-                    match key {
-                        Keycode::Right => joypad_dpad &= !0x01,
-                        Keycode::Left => joypad_dpad &= !0x02,
-                        Keycode::Up => joypad_dpad &= !0x04,
-                        Keycode::Down => joypad_dpad &= !0x08,
-                        Keycode::Z => joypad_buttons &= !0x01, // A
-                        Keycode::X => joypad_buttons &= !0x02, // B
-                        Keycode::Return => joypad_buttons &= !0x08, // Start
-                        Keycode::Backspace => joypad_buttons &= !0x04, // Select
-                        _ => {}
+                    if let Some(button) = key_button(key) {
+                        gb.set_button(button, true);
                     }
-                    memory.set_joypad(joypad_buttons, joypad_dpad);
                 }
                 Event::KeyUp {
                     keycode: Some(key), ..
                 } => {
-                    match key {
-                        Keycode::Right => joypad_dpad |= 0x01,
-                        Keycode::Left => joypad_dpad |= 0x02,
-                        Keycode::Up => joypad_dpad |= 0x04,
-                        Keycode::Down => joypad_dpad |= 0x08,
-                        Keycode::Z => joypad_buttons |= 0x01,
-                        Keycode::X => joypad_buttons |= 0x02,
-                        Keycode::Return => joypad_buttons |= 0x08,
-                        Keycode::Backspace => joypad_buttons |= 0x04,
-                        _ => {}
+                    if let Some(button) = key_button(key) {
+                        gb.set_button(button, false);
                     }
-                    memory.set_joypad(joypad_buttons, joypad_dpad);
                 }
                 _ => {}
             }
         }
 
-        // Run CPU + GPU until a full frame (VBlank) is ready.
-        // After each CPU step, tick the APU and push any new samples.
-        let framebuffer = loop {
-            let (time_increment, _) = cpu.step(&mut memory);
-            cpu.handle_interrupts(&mut memory);
-
-            // Tick APU with the T-cycle count this instruction took
-            memory.tick_apu_into_queue(time_increment.t as u32, &sample_queue);
-
-            if let Some(fb) = gpu.step(time_increment, &mut memory) {
-                break fb;
+        // Run the CPU until a full frame (VBlank) is ready; the PPU, APU,
+        // timer and serial port are clocked as it goes.
+        if let Some(stub) = &mut gdb {
+            match stub.run_frame(&mut gb) {
+                GdbStatus::Running { .. } | GdbStatus::Paused => {}
+                GdbStatus::Detached => {
+                    println!("gdb detached");
+                    gdb = None;
+                }
+                GdbStatus::Killed => break 'running,
             }
-        };
+        } else if let Some((debugger, lines)) = &mut console {
+            run_debugged_frame(&mut gb, debugger, lines);
+        } else {
+            gb.run_frame();
+        }
+        queue_samples(&gb.take_samples(), &sample_queue);
+        for access in gb.take_blocked() {
+            eprintln!("{}", access);
+        }
 
         // Render the frame
         let mut pixels: Vec<u8> = Vec::with_capacity(160 * 144 * 4);
-        for pixel in framebuffer.0.iter() {
+        for pixel in gb.framebuffer().0.iter() {
             pixels.push(pixel.r);
             pixels.push(pixel.g);
             pixels.push(pixel.b);
@@ -171,10 +354,120 @@ fn main() {
         canvas.copy(&texture, None, None).unwrap();
         canvas.present();
 
+        // Flush battery RAM every ~5 seconds so a crash loses little progress
+        frames_since_flush += 1;
+        if frames_since_flush >= SAVE_FLUSH_FRAMES {
+            frames_since_flush = 0;
+            flush_save(&gb, &save_path, &mut last_saved);
+        }
+
         // Sleep to cap at native Game Boy framerate (~59.7 fps)
         let elapsed = frame_start.elapsed();
         if elapsed < frame_duration {
             std::thread::sleep(frame_duration - elapsed);
         }
     }
+
+    flush_save(&gb, &save_path, &mut last_saved);
+}
+
+/// Read console lines on their own thread, so the window keeps running while
+/// the console waits for input.
+fn spawn_console() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Run the console commands typed since the last frame, then a frame unless
+/// the debugger is paused.
+fn run_debugged_frame(gb: &mut GameBoy, debugger: &mut Debugger, lines: &Receiver<String>) {
+    while let Ok(line) = lines.try_recv() {
+        match debugger.command(gb, &line) {
+            Ok(output) if !output.is_empty() => println!("{}", output),
+            Ok(_) => {}
+            Err(e) => println!("{}", e),
+        }
+        if debugger.paused() {
+            prompt();
+        }
+    }
+    if let Some(stop) = debugger.run_frame(gb) {
+        println!("{}", stop);
+        println!("{}", debugger::location(gb));
+        prompt();
+    }
+}
+
+fn prompt() {
+    print!("(debug) ");
+    let _ = std::io::stdout().flush();
+}
+
+/// Push a frame's worth of APU output to the audio callback's queue.
+fn queue_samples(samples: &[i16], queue: &Arc<Mutex<VecDeque<i16>>>) {
+    if let Ok(mut q) = queue.lock() {
+        // Cap queue at ~2 frames of audio to avoid unbounded growth
+        let room = (44_100 / 30 * 2usize).saturating_sub(q.len());
+        q.extend(samples.iter().take(room & !1));
+    }
+}
+
+/// Write battery-backed RAM to `path` if it changed since the last write.
+fn flush_save(gb: &GameBoy, path: &Path, last_saved: &mut Option<Vec<u8>>) {
+    let Some(data) = gb.save_data() else {
+        return;
+    };
+    if last_saved.as_ref() == Some(&data) {
+        return;
+    }
+    match std::fs::write(path, &data) {
+        Ok(()) => *last_saved = Some(data),
+        Err(e) => eprintln!("Failed to write save '{}': {}", path.display(), e),
+    }
+}
+
+/// Game Boy button for a keyboard key.
+fn key_button(key: Keycode) -> Option<Button> {
+    let button = match key {
+        Keycode::Right => Button::Right,
+        Keycode::Left => Button::Left,
+        Keycode::Up => Button::Up,
+        Keycode::Down => Button::Down,
+        Keycode::Z => Button::A,
+        Keycode::X => Button::B,
+        Keycode::Return => Button::Start,
+        Keycode::Backspace => Button::Select,
+        _ => return None,
+    };
+    Some(button)
+}
+
+/// Save state slot selected by number keys 1-9.
+fn state_slot_key(key: Keycode) -> Option<u8> {
+    let slot = match key {
+        Keycode::Num1 => 1,
+        Keycode::Num2 => 2,
+        Keycode::Num3 => 3,
+        Keycode::Num4 => 4,
+        Keycode::Num5 => 5,
+        Keycode::Num6 => 6,
+        Keycode::Num7 => 7,
+        Keycode::Num8 => 8,
+        Keycode::Num9 => 9,
+        _ => return None,
+    };
+    Some(slot)
+}
+
+/// Save states live next to the ROM as <rom>.ss1 ... <rom>.ss9.
+fn state_path(rom_path: &str, slot: u8) -> PathBuf {
+    Path::new(rom_path).with_extension(format!("ss{}", slot))
 }
@@ -0,0 +1,439 @@
+// MBC3 mapper with real-time clock.
+//
+//   0x0000–0x1FFF  RAM and RTC enable (0x0A)
+//   0x2000–0x3FFF  ROM bank, 7 bits (0 is translated to 1)
+//   0x4000–0x5FFF  RAM bank 0–3, or RTC register 0x08–0x0C
+//   0x6000–0x7FFF  latch clock data: write 0x00 then 0x01
+//
+// The RTC counts wall-clock seconds from a `TimeSource`. The CPU never reads
+// the live counters directly: latching copies them into a snapshot that stays
+// stable until the next latch.
+
+use crate::cartridge::{banked_ram_offset, load_ram, read_banked_rom, Mbc};
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+/// Wall-clock provider for the RTC, injectable so tests can control time.
+pub trait TimeSource {
+    /// Current time in whole seconds since the Unix epoch.
+    fn now(&self) -> u64;
+}
+
+/// The host's real clock.
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    // std::time::SystemTime is unavailable in the browser
+    #[cfg(target_arch = "wasm32")]
+    fn now(&self) -> u64 {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+}
+
+// RTC register indices, as selected through 0x4000–0x5FFF
+const RTC_S: usize = 0;
+const RTC_M: usize = 1;
+const RTC_H: usize = 2;
+const RTC_DL: usize = 3;
+const RTC_DH: usize = 4;
+
+// Size of the RTC block appended to `.sav` files, with a 64- or 32-bit timestamp
+const RTC_FOOTER_LEN: usize = 48;
+const RTC_FOOTER_LEN_SHORT: usize = 44;
+
+pub struct Rtc {
+    // Live counters: S, M, H, DL, DH (DH bit 0 = day bit 8, bit 6 = halt, bit 7 = carry)
+    registers: [u8; 5],
+    latched: [u8; 5],
+    // Wall-clock second the live counters were last brought up to date
+    last_sync: u64,
+    source: Box<dyn TimeSource>,
+}
+
+impl Rtc {
+    pub fn new(source: Box<dyn TimeSource>) -> Self {
+        let last_sync = source.now();
+        Rtc {
+            registers: [0; 5],
+            latched: [0; 5],
+            last_sync,
+            source,
+        }
+    }
+
+    pub fn set_time_source(&mut self, source: Box<dyn TimeSource>) {
+        self.sync();
+        self.last_sync = source.now();
+        self.source = source;
+    }
+
+    fn halted(&self) -> bool {
+        self.registers[RTC_DH] & 0x40 != 0
+    }
+
+    fn days(&self) -> u64 {
+        (((self.registers[RTC_DH] & 0x01) as u64) << 8) | self.registers[RTC_DL] as u64
+    }
+
+    /// Bring the live counters up to the time source's current time.
+    pub fn sync(&mut self) {
+        let now = self.source.now();
+        let elapsed = now.saturating_sub(self.last_sync);
+        self.last_sync = now;
+        if !self.halted() && elapsed > 0 {
+            self.advance(elapsed);
+        }
+    }
+
+    fn advance(&mut self, seconds: u64) {
+        let total = seconds
+            + self.registers[RTC_S] as u64
+            + self.registers[RTC_M] as u64 * 60
+            + self.registers[RTC_H] as u64 * 3600
+            + self.days() * 86400;
+        let mut days = total / 86400;
+        self.registers[RTC_S] = (total % 60) as u8;
+        self.registers[RTC_M] = (total / 60 % 60) as u8;
+        self.registers[RTC_H] = (total / 3600 % 24) as u8;
+        let mut dh = self.registers[RTC_DH] & 0xC0;
+        if days > 511 {
+            // Day counter overflow sets the sticky carry bit
+            dh |= 0x80;
+            days %= 512;
+        }
+        self.registers[RTC_DL] = days as u8;
+        self.registers[RTC_DH] = dh | ((days >> 8) as u8 & 0x01);
+    }
+
+    /// Serialize as the 48-byte RTC block appended to `.sav` files (VBA/BGB
+    /// layout): live S, M, H, DL, DH and the latched copies as little-endian
+    /// u32s, then the Unix timestamp they were valid at as a u64.
+    pub fn save_footer(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(RTC_FOOTER_LEN);
+        for value in self.registers.iter().chain(self.latched.iter()) {
+            out.extend_from_slice(&(*value as u32).to_le_bytes());
+        }
+        out.extend_from_slice(&self.last_sync.to_le_bytes());
+        out
+    }
+
+    /// Restore from an RTC block written by `save_footer`. Some emulators
+    /// store a 32-bit timestamp, giving a 44-byte block. Time that passed
+    /// while the emulator was closed is applied on the next access.
+    pub fn load_footer(&mut self, footer: &[u8]) {
+        if footer.len() < RTC_FOOTER_LEN_SHORT {
+            return;
+        }
+        // Each register is stored as a u32; only its low byte is meaningful
+        let field = |i: usize| footer[i * 4];
+        for i in 0..5 {
+            self.registers[i] = field(i);
+            self.latched[i] = field(i + 5);
+        }
+        let timestamp = &footer[40..];
+        self.last_sync = if timestamp.len() >= 8 {
+            u64::from_le_bytes(timestamp[..8].try_into().unwrap())
+        } else {
+            u32::from_le_bytes(timestamp[..4].try_into().unwrap()) as u64
+        };
+    }
+
+    /// Save-state form of the clock. As with `.sav` files, the counters are
+    /// stored with the timestamp they were valid at, so wall-clock time keeps
+    /// counting across save and load.
+    pub fn save_state(&self, w: &mut StateWriter) {
+        for value in self.registers.iter().chain(self.latched.iter()) {
+            w.u8(*value);
+        }
+        w.u64(self.last_sync);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        for value in self.registers.iter_mut().chain(self.latched.iter_mut()) {
+            *value = r.u8()?;
+        }
+        self.last_sync = r.u64()?;
+        Ok(())
+    }
+
+    /// Copy the live counters into the CPU-visible snapshot.
+    pub fn latch(&mut self) {
+        self.sync();
+        self.latched = self.registers;
+    }
+
+    /// Read RTC register 0x08–0x0C from the latched snapshot.
+    pub fn read(&self, select: u8) -> u8 {
+        match select {
+            0x08..=0x0C => self.latched[(select - 0x08) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    /// Write RTC register 0x08–0x0C (sets the live counters).
+    pub fn write(&mut self, select: u8, value: u8) {
+        self.sync();
+        match select {
+            0x08 => self.registers[RTC_S] = value & 0x3F,
+            0x09 => self.registers[RTC_M] = value & 0x3F,
+            0x0A => self.registers[RTC_H] = value & 0x1F,
+            0x0B => self.registers[RTC_DL] = value,
+            0x0C => self.registers[RTC_DH] = value & 0xC1,
+            _ => {}
+        }
+    }
+}
+
+pub struct Mbc3 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    rom_bank: u8,
+    // 0x00–0x03 selects a RAM bank, 0x08–0x0C an RTC register
+    ram_select: u8,
+    // Last value written to the latch register, to detect the 0x00 → 0x01 sequence
+    latch_last: u8,
+    rtc: Rtc,
+}
+
+impl Mbc3 {
+    pub fn new(rom: Vec<u8>, ram: Vec<u8>) -> Self {
+        Mbc3 {
+            rom,
+            ram,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_select: 0,
+            latch_last: 0xFF,
+            rtc: Rtc::new(Box::new(SystemClock)),
+        }
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        let bank = if addr < 0x4000 {
+            0
+        } else {
+            self.rom_bank as usize
+        };
+        read_banked_rom(&self.rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                let bank = value & 0x7F;
+                self.rom_bank = if bank == 0 { 1 } else { bank };
+            }
+            0x4000..=0x5FFF => self.ram_select = value,
+            _ => {
+                if self.latch_last == 0x00 && value == 0x01 {
+                    self.rtc.latch();
+                }
+                self.latch_last = value;
+            }
+        }
+    }
+
+    /// Banked RAM or the selected RTC register.
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        match self.ram_select {
+            0x00..=0x03 if !self.ram.is_empty() => {
+                self.ram[banked_ram_offset(&self.ram, self.ram_select as usize, addr)]
+            }
+            0x08..=0x0C => self.rtc.read(self.ram_select),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        match self.ram_select {
+            0x00..=0x03 if !self.ram.is_empty() => {
+                let offset = banked_ram_offset(&self.ram, self.ram_select as usize, addr);
+                self.ram[offset] = value;
+            }
+            0x08..=0x0C => self.rtc.write(self.ram_select, value),
+            _ => {}
+        }
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        let mut out = self.ram.clone();
+        out.extend(self.rtc.save_footer());
+        out
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        load_ram(&mut self.ram, data);
+        if data.len() > self.ram.len() {
+            self.rtc.load_footer(&data[self.ram.len()..]);
+        }
+    }
+
+    fn set_rtc_time_source(&mut self, source: Box<dyn TimeSource>) {
+        self.rtc.set_time_source(source);
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bool(self.ram_enabled);
+        w.u8(self.rom_bank);
+        w.u8(self.ram_select);
+        w.u8(self.latch_last);
+        w.bytes(&self.ram);
+        self.rtc.save_state(w);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.ram_enabled = r.bool()?;
+        self.rom_bank = r.u8()? & 0x7F;
+        self.ram_select = r.u8()?;
+        self.latch_last = r.u8()?;
+        r.bytes_into(&mut self.ram)?;
+        self.rtc.load_state(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A clock the test moves by hand.
+    struct FakeClock(Rc<Cell<u64>>);
+
+    impl TimeSource for FakeClock {
+        fn now(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
+    /// An MBC3 with RAM and the RTC enabled, on a fake clock at 1000 s.
+    fn mbc3() -> (Mbc3, Rc<Cell<u64>>) {
+        let time = Rc::new(Cell::new(1000));
+        let mut mbc = Mbc3::new(vec![0; 0x8000], vec![0; 0x2000]);
+        mbc.set_rtc_time_source(Box::new(FakeClock(Rc::clone(&time))));
+        mbc.write_rom(0x0000, 0x0A);
+        (mbc, time)
+    }
+
+    fn latch(mbc: &mut Mbc3) {
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+    }
+
+    fn read_rtc(mbc: &mut Mbc3, select: u8) -> u8 {
+        mbc.write_rom(0x4000, select);
+        mbc.read_ram(0xA000)
+    }
+
+    fn write_rtc(mbc: &mut Mbc3, select: u8, value: u8) {
+        mbc.write_rom(0x4000, select);
+        mbc.write_ram(0xA000, value);
+    }
+
+    /// Latched S, M, H, DL, DH.
+    fn latched(mbc: &mut Mbc3) -> [u8; 5] {
+        latch(mbc);
+        [0x08, 0x09, 0x0A, 0x0B, 0x0C].map(|select| read_rtc(mbc, select))
+    }
+
+    #[test]
+    fn registers_only_change_on_a_zero_then_one_latch() {
+        let (mut mbc, time) = mbc3();
+        time.set(1005);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 5);
+
+        time.set(1008);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 5);
+        // 0x01 again without 0x00 first is not a latch
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 5);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 8);
+    }
+
+    #[test]
+    fn seconds_minutes_and_hours_roll_over_into_days() {
+        let (mut mbc, time) = mbc3();
+        write_rtc(&mut mbc, 0x08, 59);
+        time.set(1001);
+        assert_eq!(latched(&mut mbc), [0, 1, 0, 0, 0]);
+
+        write_rtc(&mut mbc, 0x08, 59);
+        write_rtc(&mut mbc, 0x09, 59);
+        write_rtc(&mut mbc, 0x0A, 23);
+        time.set(1002);
+        assert_eq!(latched(&mut mbc), [0, 0, 0, 1, 0]);
+
+        write_rtc(&mut mbc, 0x0B, 0xFF);
+        time.set(1002 + 86400);
+        assert_eq!(latched(&mut mbc), [0, 0, 0, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn day_counter_overflow_wraps_and_sets_the_sticky_carry() {
+        let (mut mbc, time) = mbc3();
+        write_rtc(&mut mbc, 0x08, 59);
+        write_rtc(&mut mbc, 0x09, 59);
+        write_rtc(&mut mbc, 0x0A, 23);
+        write_rtc(&mut mbc, 0x0B, 0xFF);
+        write_rtc(&mut mbc, 0x0C, 0x01); // day 511
+        time.set(1001);
+        assert_eq!(latched(&mut mbc), [0, 0, 0, 0x00, 0x80]);
+
+        time.set(1001 + 86400);
+        assert_eq!(latched(&mut mbc), [0, 0, 0, 0x01, 0x80]);
+    }
+
+    #[test]
+    fn halt_stops_the_clock() {
+        let (mut mbc, time) = mbc3();
+        write_rtc(&mut mbc, 0x0C, 0x40);
+        time.set(1100);
+        assert_eq!(latched(&mut mbc), [0, 0, 0, 0, 0x40]);
+
+        // Time spent halted is not made up afterwards
+        write_rtc(&mut mbc, 0x0C, 0x00);
+        time.set(1102);
+        assert_eq!(latched(&mut mbc), [2, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn save_footer_round_trips_and_counts_time_while_closed() {
+        let (mut mbc, time) = mbc3();
+        write_rtc(&mut mbc, 0x09, 30);
+        time.set(1010);
+        let before = latched(&mut mbc);
+        assert_eq!(before, [10, 30, 0, 0, 0]);
+        let save = mbc.save_data();
+        assert_eq!(save.len(), 0x2000 + RTC_FOOTER_LEN);
+
+        let (mut restored, later) = mbc3();
+        later.set(1010);
+        restored.load_save_data(&save);
+        assert_eq!(
+            [0x08, 0x09, 0x0A, 0x0B, 0x0C].map(|select| read_rtc(&mut restored, select)),
+            before
+        );
+        later.set(1025);
+        assert_eq!(latched(&mut restored), [25, 30, 0, 0, 0]);
+    }
+}
@@ -1,10 +1,15 @@
-#![allow(dead_code)] // some methods are WASM-only APIs
 use crate::apu::Apu;
-#[cfg(not(target_arch = "wasm32"))]
-use std::collections::VecDeque;
+use crate::boot::{self, Model};
+use crate::cartridge::{self, CartridgeError, Mbc};
+use crate::cgb::{self, Hdma, HdmaStart, PaletteRam};
+use crate::dma::OamDma;
+use crate::header::{CgbSupport, Header};
+use crate::joypad::{Button, Joypad};
+use crate::mbc3::TimeSource;
+use crate::savestate::{StateError, StateReader, StateWriter};
+use crate::serial::{Serial, SerialDevice};
+use crate::timer::Timer;
 use std::fmt;
-#[cfg(not(target_arch = "wasm32"))]
-use std::sync::{Arc, Mutex};
 
 pub trait MemoryAccess {
     fn read_byte(&self, addr: u16) -> u8;
@@ -13,28 +18,90 @@ pub trait MemoryAccess {
     fn write_word(&mut self, addr: u16, value: u16);
     fn generate_tileset_rgba(&self, buffer: &mut [u8]);
     fn generate_memory_rgba(&self, buffer: &mut [u8]);
-    /// Update joypad state. buttons/dpad: bit=0 means pressed (active-low).
-    fn set_joypad(&mut self, buttons: u8, dpad: u8);
+    /// Press or release a joypad button, raising the joypad interrupt if the game can see the press.
+    fn set_button(&mut self, button: Button, pressed: bool);
+    /// Buttons currently held, as a `Button::mask` bitmask.
+    fn held_buttons(&self) -> u8;
+    /// Set every button at once from a `Button::mask` bitmask.
+    fn set_held_buttons(&mut self, held: u8);
     /// Tick APU by `cycles` T-cycles; returns a stereo sample when one is ready.
     fn tick_apu_sample(&mut self, cycles: u32) -> Option<(i16, i16)>;
-    /// Tick APU by `cycles` T-cycles, pushing any generated samples into the queue.
-    #[cfg(not(target_arch = "wasm32"))]
-    fn tick_apu_into_queue(&mut self, cycles: u32, queue: &Arc<Mutex<VecDeque<i16>>>);
+    /// Battery-backed cartridge RAM (plus RTC block) in `.sav` format, or None
+    /// when the cartridge has no battery.
+    fn save_data(&self) -> Option<Vec<u8>>;
+    /// Restore battery-backed cartridge RAM from a `.sav` file.
+    fn load_save_data(&mut self, data: &[u8]);
+    /// Whether the cartridge's rumble motor is on (MBC5 rumble carts).
+    fn rumble(&self) -> bool;
+    /// Update the read-only STAT bits written by the PPU: mode (bits 0-1) and LYC=LY (bit 2).
+    fn update_stat(&mut self, mode: u8, coincidence: bool);
+    /// Tick the clocked IO blocks (timer, serial) by `cycles` T-cycles, raising their interrupts in IF.
+    fn tick_io(&mut self, cycles: u32);
+    /// Write RAM, IO registers, APU, timer, serial and cartridge state into a save state.
+    fn save_state(&self, w: &mut StateWriter);
+    /// Restore everything written by `save_state`.
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError>;
+    /// Whether the machine runs in CGB mode (CGB hardware with a CGB-capable cartridge).
+    fn cgb_mode(&self) -> bool;
+    /// Read VRAM bank 0 or 1 directly, whatever bank VBK maps for the CPU.
+    fn read_vram(&self, bank: usize, addr: u16) -> u8;
+    /// CGB colour `color_id` of a background (`obj` false) or object palette, as BGR555.
+    fn cgb_color(&self, obj: bool, palette: u8, color_id: u8) -> u16;
+    /// Whether the CPU runs at double speed (CGB).
+    fn double_speed(&self) -> bool;
+    /// Switch CPU speed if KEY1 has a switch prepared; STOP calls this. Returns whether it switched.
+    fn speed_switch(&mut self) -> bool;
+    /// The PPU entered HBlank on a visible line: copy the next block of an HBlank DMA.
+    fn hblank(&mut self);
+    /// T-cycles the CPU has been paused for (HDMA, speed switch) since the last call.
+    fn take_stall_cycles(&mut self) -> u32;
+    /// Whether the PPU keeps the CPU off `addr` in its current mode: OAM during
+    /// modes 2 and 3, VRAM during mode 3. Blocked reads return 0xFF and blocked
+    /// writes are dropped.
+    fn ppu_blocks(&self, addr: u16) -> bool;
+    /// Whether an OAM DMA transfer is starting or under way.
+    fn oam_dma_active(&self) -> bool;
+    /// What a CPU read of `addr` returns while OAM DMA holds its bus, or None
+    /// if the access goes through. Conflicting writes are lost.
+    fn oam_dma_conflict(&self, addr: u16) -> Option<u8>;
+    /// Attach a device to the other end of the link cable.
+    fn set_serial_device(&mut self, device: Box<dyn SerialDevice>);
 }
 
+// T-cycles the CPU is stopped for while switching speed
+const SPEED_SWITCH_CYCLES: u32 = 2050 * 4;
+
 pub struct Memory {
-    pub bios: [u8; 256],
-    rom: Vec<u8>,
+    // Boot ROM, if one was supplied; mapped while `bios_enabled`
+    bios: Vec<u8>,
     the_rest: [u8; 49152],
     bios_enabled: bool,
-    // MBC1 bank switching
-    mbc_type: u8,
-    rom_bank: usize,
-    // Joypad state: 0=pressed, 1=released (active low)
-    pub joypad_buttons: u8,
-    pub joypad_dpad: u8,
-    joypad_select: u8,
+    // Cartridge ROM/RAM and memory bank controller
+    cartridge: Box<dyn Mbc>,
+    header: Header,
+    // CGB mode; the fields below only take effect while it is on
+    cgb: bool,
+    // VRAM bank 1; bank 0 is in `the_rest`
+    vram_bank1: Vec<u8>,
+    // VBK: VRAM bank the CPU sees at 0x8000-0x9FFF
+    vram_bank: u8,
+    // WRAM banks 2-7; banks 0 and 1 are in `the_rest`
+    wram_banks: Vec<u8>,
+    // SVBK: WRAM bank at 0xD000-0xDFFF (0 selects 1)
+    wram_bank: u8,
+    // KEY1: current speed and whether STOP should switch it
+    double_speed: bool,
+    speed_switch_armed: bool,
+    bg_palettes: PaletteRam,
+    obj_palettes: PaletteRam,
+    hdma: Hdma,
+    oam_dma: OamDma,
+    // T-cycles the CPU is paused while the rest of the system runs
+    stall_cycles: u32,
+    pub joypad: Joypad,
     pub apu: Apu,
+    pub timer: Timer,
+    pub serial: Serial,
 }
 
 impl fmt::Debug for dyn MemoryAccess {
@@ -52,92 +119,232 @@ impl fmt::Debug for dyn MemoryAccess {
 }
 
 impl Memory {
-    pub fn initialize(rom_path: &str) -> Self {
-        let bios: [u8; 256] = include_bytes!("../bios/bios.rom").clone();
-        let rom: Vec<u8> = std::fs::read(rom_path)
-            .unwrap_or_else(|e| panic!("Failed to read ROM '{}': {}", rom_path, e));
-        let mbc_type = if rom.len() > 0x148 { rom[0x147] } else { 0 };
-        Self {
-            bios,
-            mbc_type,
-            rom_bank: 1,
-            rom,
-            the_rest: [0; 49152],
-            bios_enabled: true,
-            joypad_buttons: 0xFF,
-            joypad_dpad: 0xFF,
-            joypad_select: 0x30,
-            apu: Apu::new(),
-        }
+    pub fn initialize(rom_path: &str) -> Result<Self, CartridgeError> {
+        let rom = std::fs::read(rom_path).map_err(|e| CartridgeError::Io(e.to_string()))?;
+        Self::initialize_with_rom(rom)
     }
 
     /// Initialize with ROM data provided at runtime (used by WASM frontend).
-    pub fn initialize_with_rom(rom_data: Vec<u8>) -> Self {
-        let bios: [u8; 256] = include_bytes!("../bios/bios.rom").clone();
-        let mbc_type = if rom_data.len() > 0x148 {
-            rom_data[0x147]
-        } else {
-            0
-        };
-        Self {
-            bios,
-            mbc_type,
-            rom_bank: 1,
-            rom: rom_data,
+    /// Fails if the header is invalid or names an unsupported mapper.
+    /// Nothing is mapped over the cartridge yet; see `boot::power_on`.
+    pub fn initialize_with_rom(rom_data: Vec<u8>) -> Result<Self, CartridgeError> {
+        let header = Header::parse(&rom_data)?;
+        Ok(Self {
+            bios: Vec::new(),
+            cartridge: cartridge::load(rom_data, &header)?,
+            header,
             the_rest: [0; 49152],
-            bios_enabled: true,
-            joypad_buttons: 0xFF,
-            joypad_dpad: 0xFF,
-            joypad_select: 0x30,
+            bios_enabled: false,
+            cgb: false,
+            vram_bank1: vec![0; 0x2000],
+            vram_bank: 0,
+            wram_banks: vec![0; 6 * 0x1000],
+            wram_bank: 1,
+            double_speed: false,
+            speed_switch_armed: false,
+            bg_palettes: PaletteRam::new(),
+            obj_palettes: PaletteRam::new(),
+            hdma: Hdma::new(),
+            oam_dma: OamDma::new(),
+            stall_cycles: 0,
+            joypad: Joypad::new(),
             apu: Apu::new(),
+            timer: Timer::new(),
+            serial: Serial::new(),
+        })
+    }
+
+    /// Map a boot ROM over the cartridge until 0xFF50 is written. A CGB boot
+    /// ROM starts the machine in CGB mode; it drops to DMG mode through KEY0
+    /// for cartridges without CGB support.
+    pub fn map_bios(&mut self, bios: Vec<u8>) {
+        self.cgb = bios.len() == boot::CGB_BIOS_SIZE;
+        self.bios = bios;
+        self.bios_enabled = true;
+    }
+
+    /// Put IO registers, DIV and VRAM in the state the boot ROM leaves `model`
+    /// in. A CGB runs CGB-capable cartridges in CGB mode, with every
+    /// background and object colour white.
+    pub fn skip_boot(&mut self, model: Model) {
+        self.cgb = model == Model::Cgb && self.header.cgb != CgbSupport::None;
+        if self.cgb {
+            self.bg_palettes.fill_white();
+            self.obj_palettes.fill_white();
+        }
+        for (addr, value) in boot::POST_BOOT_IO {
+            self.write_byte(addr, value);
         }
+        self.timer.set_counter(boot::post_boot_div_counter(model));
+        if model != Model::Cgb {
+            let logo: Vec<u8> = (0x0104..0x0134).map(|addr| self.read_byte(addr)).collect();
+            for (addr, value) in boot::logo_vram(&logo) {
+                self.write_byte(addr, value);
+            }
+        }
+    }
+
+    /// The cartridge header, as parsed when the ROM was loaded.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Cartridge header checksum byte (0x14D).
+    pub fn header_checksum(&self) -> u8 {
+        self.header.header_checksum
+    }
+
+    /// Whether the boot ROM answers reads at `addr`: 0x0000-0x00FF, and
+    /// 0x0200-0x08FF for the larger CGB boot ROM (the cartridge header shows
+    /// through the gap).
+    fn bios_mapped(&self, addr: usize) -> bool {
+        self.bios_enabled && addr < self.bios.len() && !(0x0100..0x0200).contains(&addr)
     }
 
     /// Advance the APU by `cycles` T-cycles; returns a stereo sample when one is ready.
     pub fn tick_apu(&mut self, cycles: u32) -> Option<(i16, i16)> {
         self.apu.tick(cycles)
     }
+
+    /// Replace the wall clock driving the MBC3 real-time clock.
+    pub fn set_rtc_time_source(&mut self, source: Box<dyn TimeSource>) {
+        self.cartridge.set_rtc_time_source(source);
+    }
+
+    /// Set a bit in the interrupt flag register (IF, 0xFF0F).
+    fn request_interrupt(&mut self, bit: u8) {
+        self.the_rest[0xFF0F - 0x8000] |= 1 << bit;
+    }
+
+    /// Offset into `wram_banks` for 0xD000-0xDFFF, or None when SVBK maps
+    /// bank 1 (kept in `the_rest` like on DMG).
+    fn banked_wram_offset(&self, addr: u16) -> Option<usize> {
+        match self.wram_bank {
+            0 | 1 => None,
+            bank => Some((bank as usize - 2) * 0x1000 + (addr as usize - 0xD000)),
+        }
+    }
+
+    /// CGB-mode reads of banked memory and CGB registers; None for anything
+    /// that reads the same as on DMG.
+    fn read_cgb(&self, addr: u16) -> Option<u8> {
+        let value = match addr {
+            0x8000..=0x9FFF if self.vram_bank == 1 => self.vram_bank1[addr as usize - 0x8000],
+            0xD000..=0xDFFF => self.wram_banks[self.banked_wram_offset(addr)?],
+            0xFF4D => 0x7E | ((self.double_speed as u8) << 7) | self.speed_switch_armed as u8,
+            0xFF4F => 0xFE | self.vram_bank,
+            0xFF51..=0xFF54 => 0xFF,
+            0xFF55 => self.hdma.read_control(),
+            0xFF68 => self.bg_palettes.read_index(),
+            0xFF69 => self.bg_palettes.read_data(),
+            0xFF6A => self.obj_palettes.read_index(),
+            0xFF6B => self.obj_palettes.read_data(),
+            0xFF70 => 0xF8 | self.wram_bank,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// CGB-mode writes to banked memory and CGB registers. Returns false for
+    /// anything that behaves as on DMG.
+    fn write_cgb(&mut self, addr: u16, value: u8) -> bool {
+        match addr {
+            0x8000..=0x9FFF if self.vram_bank == 1 => {
+                self.vram_bank1[addr as usize - 0x8000] = value
+            }
+            0xD000..=0xDFFF => match self.banked_wram_offset(addr) {
+                Some(offset) => self.wram_banks[offset] = value,
+                None => return false,
+            },
+            // KEY0: the CGB boot ROM selects DMG mode for non-CGB cartridges
+            0xFF4C => {
+                if self.bios_enabled && value & 0x04 != 0 {
+                    self.cgb = false;
+                }
+            }
+            0xFF4D => self.speed_switch_armed = value & 0x01 != 0,
+            0xFF4F => self.vram_bank = value & 0x01,
+            0xFF51..=0xFF55 => match self.hdma.write(addr, value) {
+                Some(HdmaStart::General) => while self.hdma_block() {},
+                // With the LCD off there is no HBlank, so a block is copied right away
+                Some(HdmaStart::HBlank) if self.the_rest[0xFF40 - 0x8000] & 0x80 == 0 => {
+                    self.hdma_block();
+                }
+                _ => {}
+            },
+            0xFF68 => self.bg_palettes.write_index(value),
+            0xFF69 => self.bg_palettes.write_data(value),
+            0xFF6A => self.obj_palettes.write_index(value),
+            0xFF6B => self.obj_palettes.write_data(value),
+            0xFF70 => self.wram_bank = value & 0x07,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Run OAM DMA for `cycles` T-cycles, one byte per M-cycle.
+    fn tick_oam_dma(&mut self, cycles: u32) {
+        for _ in 0..self.oam_dma.m_cycles(cycles) {
+            if let Some((source, index)) = self.oam_dma.m_cycle() {
+                let byte = self.read_byte(source);
+                self.the_rest[0xFE00 - 0x8000 + index as usize] = byte;
+                self.oam_dma.copied(byte);
+            }
+        }
+    }
+
+    /// Copy the next 16-byte HDMA block into the current VRAM bank, pausing
+    /// the CPU for it. Returns false once the transfer is done.
+    fn hdma_block(&mut self) -> bool {
+        let Some((source, dest)) = self.hdma.next_block() else {
+            return false;
+        };
+        for i in 0..cgb::HDMA_BLOCK {
+            let byte = self.read_byte(source.wrapping_add(i));
+            let offset = (dest + i) as usize - 0x8000;
+            if self.vram_bank == 1 {
+                self.vram_bank1[offset] = byte;
+            } else {
+                self.the_rest[offset] = byte;
+            }
+        }
+        self.stall_cycles += cgb::HDMA_BLOCK_CYCLES << self.double_speed as u32;
+        true
+    }
 }
 
 impl MemoryAccess for Memory {
     fn read_byte(&self, addr: u16) -> u8 {
+        if self.cgb {
+            if let Some(value) = self.read_cgb(addr) {
+                return value;
+            }
+        }
         if addr == 0xFF00 {
-            // Joypad: P15(bit5)=0 selects buttons row, P14(bit4)=0 selects d-pad row
-            let select = self.joypad_select;
-            let result = if select & 0x20 == 0 {
-                // P15=low: buttons row (A=0, B=1, Select=2, Start=3)
-                0xC0 | (self.joypad_select & 0x30) | (self.joypad_buttons & 0x0F)
-            } else if select & 0x10 == 0 {
-                // P14=low: d-pad row (Right=0, Left=1, Up=2, Down=3)
-                0xC0 | (self.joypad_select & 0x30) | (self.joypad_dpad & 0x0F)
-            } else {
-                0xFF
-            };
-            return result;
+            return self.joypad.read();
+        }
+        // STAT: bit 7 is unused and always reads 1
+        if addr == 0xFF41 {
+            return 0x80 | self.the_rest[0xFF41 - 0x8000];
+        }
+        // Serial and timer registers
+        if addr == 0xFF01 || addr == 0xFF02 {
+            return self.serial.read(addr, self.cgb);
+        }
+        if (0xFF04..=0xFF07).contains(&addr) {
+            return self.timer.read(addr);
         }
         // APU register reads
         if addr >= 0xFF10 && addr <= 0xFF3F {
             return self.apu.read(addr);
         }
         let addr = addr as usize;
-        if addr < self.bios.len() && self.bios_enabled {
+        if self.bios_mapped(addr) {
             self.bios[addr]
-        } else if addr < 0x4000 {
-            // ROM bank 0 always at 0x0000-0x3FFF
-            if addr < self.rom.len() {
-                self.rom[addr]
-            } else {
-                0xFF
-            }
         } else if addr < 0x8000 {
-            // 0x4000-0x7FFF: switchable ROM bank (MBC1) or bank 1 (ROM only)
-            let bank = if self.mbc_type >= 1 { self.rom_bank } else { 1 };
-            let offset = (bank * 0x4000) + (addr - 0x4000);
-            if offset < self.rom.len() {
-                self.rom[offset]
-            } else {
-                0xFF
-            }
+            self.cartridge.read_rom(addr as u16)
+        } else if (0xA000..0xC000).contains(&addr) {
+            self.cartridge.read_ram(addr as u16)
         } else if addr < 0x10000 {
             // 0x8000+: the_rest (VRAM, WRAM, OAM, IO, HRAM)
             let rest_idx = addr - 0x8000;
@@ -156,39 +363,38 @@ impl MemoryAccess for Memory {
     }
 
     fn write_byte(&mut self, addr: u16, value: u8) {
-        if addr == 0xFF00 {
-            self.joypad_select = value & 0x30;
-            return;
-        }
-        // MBC1: ROM bank select (writes to 0x2000-0x3FFF)
-        if self.mbc_type >= 1 && addr >= 0x2000 && addr < 0x4000 {
-            let bank = (value & 0x1F) as usize;
-            self.rom_bank = if bank == 0 { 1 } else { bank };
+        if self.cgb && self.write_cgb(addr, value) {
             return;
         }
-        // MBC1: RAM enable (0x0000-0x1FFF) — ignore
-        if self.mbc_type >= 1 && addr < 0x2000 {
+        if addr == 0xFF00 {
+            if self.joypad.write(value) {
+                self.request_interrupt(4);
+            }
             return;
         }
         let addr = addr as usize;
-        if addr < self.bios.len() && self.bios_enabled {
-            // writes to BIOS region ignored
-        } else if addr < 0x8000 {
-            // ROM is read-only
+        if addr < 0x8000 {
+            // ROM (and the BIOS overlay) is read-only; writes go to the mapper's control registers
+            self.cartridge.write_rom(addr as u16, value);
+        } else if (0xA000..0xC000).contains(&addr) {
+            self.cartridge.write_ram(addr as u16, value);
         } else if addr == 0xFF50 {
             // Writing to 0xFF50 disables the BIOS
             if value != 0 {
                 self.bios_enabled = false;
             }
         } else if addr == 0xFF46 {
-            // OAM DMA transfer: copy 160 bytes from (value << 8) to 0xFE00
-            let src_base = (value as u16) << 8;
-            for i in 0..160u16 {
-                let byte = self.read_byte(src_base + i);
-                let dst = 0xFE00u16 + i;
-                let rest_idx = (dst as usize) - 0x8000;
-                self.the_rest[rest_idx] = byte;
-            }
+            // OAM DMA: copied over the next 161 M-cycles by `tick_io`; DMA reads back as written
+            self.oam_dma.start(value);
+            self.the_rest[0xFF46 - 0x8000] = value;
+        } else if addr == 0xFF41 {
+            // STAT: only the interrupt source selects (bits 3-6) are writable
+            let stat = &mut self.the_rest[0xFF41 - 0x8000];
+            *stat = (value & 0x78) | (*stat & 0x07);
+        } else if addr == 0xFF01 || addr == 0xFF02 {
+            self.serial.write(addr as u16, value, self.cgb);
+        } else if (0xFF04..=0xFF07).contains(&addr) {
+            self.timer.write(addr as u16, value);
         } else if addr >= 0xFF10 && addr <= 0xFF3F {
             // APU registers — notify APU and also write through to the_rest for readback
             self.apu.write(addr as u16, value);
@@ -282,25 +488,282 @@ impl MemoryAccess for Memory {
             }
         }
     }
-    fn set_joypad(&mut self, buttons: u8, dpad: u8) {
-        self.joypad_buttons = buttons;
-        self.joypad_dpad = dpad;
+    fn set_button(&mut self, button: Button, pressed: bool) {
+        if self.joypad.set_button(button, pressed) {
+            self.request_interrupt(4);
+        }
+    }
+
+    fn held_buttons(&self) -> u8 {
+        self.joypad.held()
+    }
+
+    fn set_held_buttons(&mut self, held: u8) {
+        if self.joypad.set_held(held) {
+            self.request_interrupt(4);
+        }
     }
 
     fn tick_apu_sample(&mut self, cycles: u32) -> Option<(i16, i16)> {
         self.apu.tick(cycles)
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn tick_apu_into_queue(&mut self, cycles: u32, queue: &Arc<Mutex<VecDeque<i16>>>) {
-        if let Some((l, r)) = self.apu.tick(cycles) {
-            if let Ok(mut q) = queue.lock() {
-                // Cap queue at ~2 frames of audio to avoid unbounded growth
-                if q.len() < 44_100 / 30 * 2 {
-                    q.push_back(l);
-                    q.push_back(r);
-                }
-            }
+    fn save_data(&self) -> Option<Vec<u8>> {
+        if self.header.has_battery() {
+            Some(self.cartridge.save_data())
+        } else {
+            None
+        }
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        self.cartridge.load_save_data(data);
+    }
+
+    fn rumble(&self) -> bool {
+        self.cartridge.rumble()
+    }
+
+    fn update_stat(&mut self, mode: u8, coincidence: bool) {
+        let stat = &mut self.the_rest[0xFF41 - 0x8000];
+        *stat = (*stat & 0x78) | ((coincidence as u8) << 2) | (mode & 0x03);
+    }
+
+    fn tick_io(&mut self, cycles: u32) {
+        if self.timer.tick(cycles) {
+            self.request_interrupt(2);
+        }
+        if self.serial.tick(cycles) {
+            self.request_interrupt(3);
+        }
+        if self.oam_dma.active() {
+            self.tick_oam_dma(cycles);
+        }
+    }
+
+    fn save_state(&self, w: &mut StateWriter) {
+        w.bytes(&self.the_rest);
+        w.bool(self.bios_enabled);
+        self.joypad.save_state(w);
+        self.timer.save_state(w);
+        self.serial.save_state(w);
+        self.apu.save_state(w);
+        self.cartridge.save_state(w);
+        w.bool(self.cgb);
+        w.bytes(&self.vram_bank1);
+        w.u8(self.vram_bank);
+        w.bytes(&self.wram_banks);
+        w.u8(self.wram_bank);
+        w.bool(self.double_speed);
+        w.bool(self.speed_switch_armed);
+        self.bg_palettes.save_state(w);
+        self.obj_palettes.save_state(w);
+        self.hdma.save_state(w);
+        self.oam_dma.save_state(w);
+    }
+
+    fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        r.bytes_into(&mut self.the_rest)?;
+        // States saved during the boot ROM need one to resume
+        self.bios_enabled = r.bool()? && !self.bios.is_empty();
+        self.joypad.load_state(r)?;
+        self.timer.load_state(r)?;
+        self.serial.load_state(r)?;
+        self.apu.load_state(r)?;
+        self.cartridge.load_state(r)?;
+        self.cgb = r.bool()?;
+        r.bytes_into(&mut self.vram_bank1)?;
+        self.vram_bank = r.u8()? & 0x01;
+        r.bytes_into(&mut self.wram_banks)?;
+        self.wram_bank = r.u8()? & 0x07;
+        self.double_speed = r.bool()?;
+        self.speed_switch_armed = r.bool()?;
+        self.bg_palettes.load_state(r)?;
+        self.obj_palettes.load_state(r)?;
+        self.hdma.load_state(r)?;
+        self.oam_dma.load_state(r)
+    }
+
+    fn cgb_mode(&self) -> bool {
+        self.cgb
+    }
+
+    fn read_vram(&self, bank: usize, addr: u16) -> u8 {
+        let offset = addr as usize - 0x8000;
+        if bank == 1 {
+            self.vram_bank1[offset]
+        } else {
+            self.the_rest[offset]
+        }
+    }
+
+    fn cgb_color(&self, obj: bool, palette: u8, color_id: u8) -> u16 {
+        if obj {
+            self.obj_palettes.color(palette, color_id)
+        } else {
+            self.bg_palettes.color(palette, color_id)
         }
     }
+
+    fn double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    fn speed_switch(&mut self) -> bool {
+        if !(self.cgb && self.speed_switch_armed) {
+            return false;
+        }
+        self.double_speed = !self.double_speed;
+        self.speed_switch_armed = false;
+        self.stall_cycles += SPEED_SWITCH_CYCLES;
+        true
+    }
+
+    fn hblank(&mut self) {
+        if self.cgb && self.hdma.hblank_active() {
+            self.hdma_block();
+        }
+    }
+
+    fn take_stall_cycles(&mut self) -> u32 {
+        std::mem::take(&mut self.stall_cycles)
+    }
+
+    fn oam_dma_active(&self) -> bool {
+        self.oam_dma.active()
+    }
+
+    fn oam_dma_conflict(&self, addr: u16) -> Option<u8> {
+        self.oam_dma.conflict(addr, self.cgb)
+    }
+
+    fn ppu_blocks(&self, addr: u16) -> bool {
+        // The PPU mirrors its mode into STAT, and reports mode 0 with the LCD off
+        let mode = self.the_rest[0xFF41 - 0x8000] & 0x03;
+        match addr {
+            0x8000..=0x9FFF => mode == 3,
+            0xFE00..=0xFE9F => mode >= 2,
+            _ => false,
+        }
+    }
+
+    fn set_serial_device(&mut self, device: Box<dyn SerialDevice>) {
+        self.serial.set_device(device);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Memory for a CGB cartridge after the CGB boot ROM, with the LCD on.
+    fn cgb_memory() -> Memory {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0143] = 0x80; // CGB support
+        let mut memory = Memory::initialize_with_rom(rom).unwrap();
+        memory.skip_boot(Model::Cgb);
+        memory
+    }
+
+    /// Fill 0xC000-0xC0FF with a pattern and point HDMA at it, copying to 0x8800.
+    fn hdma_source(memory: &mut Memory) {
+        for i in 0..0x100u16 {
+            memory.write_byte(0xC000 + i, 0x80 | i as u8);
+        }
+        for (addr, value) in [
+            (0xFF51, 0xC0),
+            (0xFF52, 0x00),
+            (0xFF53, 0x08),
+            (0xFF54, 0x00),
+        ] {
+            memory.write_byte(addr, value);
+        }
+    }
+
+    /// Number of bytes from 0x8800 on that hold the HDMA source pattern.
+    fn copied(memory: &Memory) -> u16 {
+        (0..0x100u16)
+            .take_while(|&i| memory.read_byte(0x8800 + i) == 0x80 | i as u8)
+            .count() as u16
+    }
+
+    #[test]
+    fn finished_serial_transfer_raises_the_serial_interrupt() {
+        let mut memory = Memory::initialize_with_rom(vec![0; 0x8000]).unwrap();
+        memory.write_byte(0xFF0F, 0x00);
+        memory.write_byte(0xFF02, 0x81);
+        memory.tick_io(4095);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x08, 0);
+        memory.tick_io(1);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x08, 0x08);
+        assert_eq!(memory.read_byte(0xFF02), 0x7F);
+    }
+
+    #[test]
+    fn svbk_zero_maps_bank_1() {
+        let mut memory = cgb_memory();
+        memory.write_byte(0xFF70, 0x00);
+        assert_eq!(memory.read_byte(0xFF70), 0xF8);
+        memory.write_byte(0xD000, 0x11);
+        memory.write_byte(0xFF70, 0x01);
+        assert_eq!(memory.read_byte(0xD000), 0x11);
+        memory.write_byte(0xFF70, 0x02);
+        assert_eq!(memory.read_byte(0xD000), 0x00);
+        memory.write_byte(0xD000, 0x22);
+        memory.write_byte(0xFF70, 0x00);
+        assert_eq!(memory.read_byte(0xD000), 0x11);
+    }
+
+    #[test]
+    fn vbk_switches_reads_to_bank_1() {
+        let mut memory = cgb_memory();
+        memory.write_byte(0xFF40, 0x00);
+        memory.write_byte(0x8000, 0x11);
+        memory.write_byte(0xFF4F, 0x01);
+        assert_eq!(memory.read_byte(0xFF4F), 0xFF);
+        assert_eq!(memory.read_byte(0x8000), 0x00);
+        memory.write_byte(0x8000, 0x22);
+        assert_eq!(memory.read_byte(0x8000), 0x22);
+        memory.write_byte(0xFF4F, 0xFE);
+        assert_eq!(memory.read_byte(0xFF4F), 0xFE);
+        assert_eq!(memory.read_byte(0x8000), 0x11);
+    }
+
+    #[test]
+    fn general_purpose_hdma_copies_the_whole_length_at_once() {
+        let mut memory = cgb_memory();
+        hdma_source(&mut memory);
+        memory.write_byte(0xFF55, 0x02);
+        assert_eq!(copied(&memory), 3 * cgb::HDMA_BLOCK);
+        assert_eq!(memory.read_byte(0xFF55), 0xFF);
+        assert_eq!(memory.take_stall_cycles(), 3 * cgb::HDMA_BLOCK_CYCLES);
+    }
+
+    #[test]
+    fn hblank_hdma_copies_one_block_per_hblank() {
+        let mut memory = cgb_memory();
+        hdma_source(&mut memory);
+        memory.write_byte(0xFF55, 0x82);
+        assert_eq!(copied(&memory), 0);
+        assert_eq!(memory.read_byte(0xFF55), 0x02);
+        for blocks in 1..=3 {
+            memory.hblank();
+            assert_eq!(copied(&memory), blocks * cgb::HDMA_BLOCK);
+        }
+        assert_eq!(memory.read_byte(0xFF55), 0xFF);
+        memory.hblank();
+        assert_eq!(copied(&memory), 3 * cgb::HDMA_BLOCK);
+    }
+
+    #[test]
+    fn cancelled_hblank_hdma_reads_back_the_blocks_left() {
+        let mut memory = cgb_memory();
+        hdma_source(&mut memory);
+        memory.write_byte(0xFF55, 0x83);
+        memory.hblank();
+        memory.write_byte(0xFF55, 0x00);
+        assert_eq!(memory.read_byte(0xFF55), 0x82);
+        memory.hblank();
+        assert_eq!(copied(&memory), cgb::HDMA_BLOCK);
+    }
 }
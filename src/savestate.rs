@@ -0,0 +1,354 @@
+// Save states — a versioned, self-describing binary snapshot of the machine.
+//
+// Layout (all integers little-endian):
+//
+//   "GBSS"                    magic
+//   u16 version               bumped whenever any section's contents change
+//   repeated sections:
+//     [u8; 4] tag             e.g. "CPU ", "GPU ", "MEM "
+//     u32 length
+//     length bytes of payload
+//
+// Each component writes and reads its own section. Sections are located by
+// tag, so the order in the file does not matter and unknown sections are
+// skipped. States written by a different version are rejected up front,
+// before anything is modified.
+
+use crate::cpu::Cpu;
+use crate::gpu::Gpu;
+use crate::memory::MemoryAccess;
+use std::fmt;
+
+const MAGIC: &[u8; 4] = b"GBSS";
+pub const VERSION: u16 = 5;
+
+const META: [u8; 4] = *b"META";
+const CPU: [u8; 4] = *b"CPU ";
+const GPU: [u8; 4] = *b"GPU ";
+const MEMORY: [u8; 4] = *b"MEM ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The data does not start with the save-state magic.
+    NotASaveState,
+    /// The state was written by a different (older or newer) format version.
+    UnsupportedVersion(u16),
+    /// The state was taken with a different ROM loaded.
+    RomMismatch,
+    /// A required section is absent.
+    MissingSection(String),
+    /// The data ends in the middle of a section or field.
+    Truncated,
+    /// A field holds a value that cannot occur in a valid state.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::NotASaveState => write!(f, "not a save state"),
+            StateError::UnsupportedVersion(v) => write!(
+                f,
+                "save state version {} is not supported (expected {})",
+                v, VERSION
+            ),
+            StateError::RomMismatch => write!(f, "save state belongs to a different ROM"),
+            StateError::MissingSection(tag) => write!(f, "save state has no '{}' section", tag),
+            StateError::Truncated => write!(f, "save state is truncated"),
+            StateError::Invalid(what) => write!(f, "save state has an invalid {}", what),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+// ---------------------------------------------------------------------------
+// Writer
+// ---------------------------------------------------------------------------
+
+pub struct StateWriter {
+    buf: Vec<u8>,
+}
+
+impl StateWriter {
+    fn new() -> Self {
+        let mut buf = Vec::with_capacity(128 * 1024);
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        StateWriter { buf }
+    }
+
+    fn section(&mut self, tag: [u8; 4], write: impl FnOnce(&mut StateWriter)) {
+        self.buf.extend_from_slice(&tag);
+        // Length placeholder, patched once the payload is written
+        let start = self.buf.len();
+        self.buf.extend_from_slice(&[0; 4]);
+        write(self);
+        let len = (self.buf.len() - start - 4) as u32;
+        self.buf[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.buf.push(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn i32(&mut self, value: i32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Length-prefixed byte string.
+    pub fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.buf.extend_from_slice(value);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reader
+// ---------------------------------------------------------------------------
+
+/// Cursor over one section's payload.
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> StateReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        let end = self.pos.checked_add(len).ok_or(StateError::Truncated)?;
+        let slice = self.data.get(self.pos..end).ok_or(StateError::Truncated)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, StateError> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, StateError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Length-prefixed byte string.
+    pub fn bytes(&mut self) -> Result<&'a [u8], StateError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    /// Length-prefixed byte string that must exactly fill `out`.
+    pub fn bytes_into(&mut self, out: &mut [u8]) -> Result<(), StateError> {
+        let bytes = self.bytes()?;
+        if bytes.len() != out.len() {
+            return Err(StateError::Invalid("buffer length"));
+        }
+        out.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// A section's tag and payload.
+type Section<'a> = ([u8; 4], &'a [u8]);
+
+/// Split a save state into its sections after checking magic and version.
+fn parse_sections(data: &[u8]) -> Result<Vec<Section<'_>>, StateError> {
+    if data.len() < 6 || &data[..4] != MAGIC {
+        return Err(StateError::NotASaveState);
+    }
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    if version != VERSION {
+        return Err(StateError::UnsupportedVersion(version));
+    }
+    let mut sections = Vec::new();
+    let mut reader = StateReader { data, pos: 6 };
+    while reader.pos < data.len() {
+        let tag: [u8; 4] = reader.take(4)?.try_into().unwrap();
+        let len = reader.u32()? as usize;
+        sections.push((tag, reader.take(len)?));
+    }
+    Ok(sections)
+}
+
+fn find_section<'a>(sections: &[Section<'a>], tag: [u8; 4]) -> Result<StateReader<'a>, StateError> {
+    sections
+        .iter()
+        .find(|(t, _)| *t == tag)
+        .map(|(_, data)| StateReader { data, pos: 0 })
+        .ok_or_else(|| StateError::MissingSection(String::from_utf8_lossy(&tag).into_owned()))
+}
+
+/// Header and global checksums (0x014D–0x014F), used to tie a state to its ROM.
+fn rom_fingerprint(memory: &dyn MemoryAccess) -> [u8; 3] {
+    [
+        memory.read_byte(0x014D),
+        memory.read_byte(0x014E),
+        memory.read_byte(0x014F),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Whole-machine snapshots
+// ---------------------------------------------------------------------------
+
+/// Snapshot the CPU, PPU, memory (including APU, timers and cartridge).
+pub fn save_state(cpu: &Cpu, gpu: &Gpu, memory: &dyn MemoryAccess) -> Vec<u8> {
+    let mut w = StateWriter::new();
+    w.section(META, |w| {
+        for byte in rom_fingerprint(memory) {
+            w.u8(byte);
+        }
+    });
+    w.section(CPU, |w| cpu.save_state(w));
+    w.section(GPU, |w| gpu.save_state(w));
+    w.section(MEMORY, |w| memory.save_state(w));
+    w.buf
+}
+
+/// Restore a snapshot taken by `save_state`. The header, version, ROM and
+/// section list are all validated before any component is touched; if a
+/// section turns out to be corrupt part-way through, the machine is rolled
+/// back to where it was.
+pub fn load_state(
+    data: &[u8],
+    cpu: &mut Cpu,
+    gpu: &mut Gpu,
+    memory: &mut dyn MemoryAccess,
+) -> Result<(), StateError> {
+    let sections = parse_sections(data)?;
+    let mut meta = find_section(&sections, META)?;
+    let fingerprint = [meta.u8()?, meta.u8()?, meta.u8()?];
+    if fingerprint != rom_fingerprint(memory) {
+        return Err(StateError::RomMismatch);
+    }
+    let backup = save_state(cpu, gpu, memory);
+    restore(&sections, cpu, gpu, memory).inspect_err(|_| {
+        let sections = parse_sections(&backup).expect("backup state is well formed");
+        restore(&sections, cpu, gpu, memory).expect("backup state restores");
+    })
+}
+
+fn restore(
+    sections: &[Section],
+    cpu: &mut Cpu,
+    gpu: &mut Gpu,
+    memory: &mut dyn MemoryAccess,
+) -> Result<(), StateError> {
+    let mut cpu_section = find_section(sections, CPU)?;
+    let mut gpu_section = find_section(sections, GPU)?;
+    let mut memory_section = find_section(sections, MEMORY)?;
+
+    cpu.load_state(&mut cpu_section)?;
+    gpu.load_state(&mut gpu_section)?;
+    memory.load_state(&mut memory_section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Memory;
+
+    fn machine() -> (Cpu, Gpu, Memory) {
+        let memory = Memory::initialize_with_rom(vec![0; 0x8000]).unwrap();
+        (Cpu::initialize(), Gpu::initialize(), memory)
+    }
+
+    /// Reassemble a state from its sections, passing each payload through `edit`.
+    fn rebuild(data: &[u8], edit: impl Fn([u8; 4], &[u8]) -> Vec<u8>) -> Vec<u8> {
+        let mut out = data[..6].to_vec();
+        for (tag, payload) in parse_sections(data).unwrap() {
+            let payload = edit(tag, payload);
+            out.extend_from_slice(&tag);
+            out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            out.extend_from_slice(&payload);
+        }
+        out
+    }
+
+    #[test]
+    fn save_then_load_restores_the_machine() {
+        let (mut cpu, mut gpu, mut memory) = machine();
+        cpu.registers_mut().a = 0x12;
+        cpu.registers_mut().program_counter = 0x0150;
+        memory.write_byte(0xC000, 0x42);
+        memory.write_byte(0xFF80, 0x99);
+        let state = save_state(&cpu, &gpu, &memory);
+
+        cpu.registers_mut().a = 0x34;
+        cpu.registers_mut().program_counter = 0x0200;
+        memory.write_byte(0xC000, 0x00);
+        memory.write_byte(0xFF80, 0x00);
+        load_state(&state, &mut cpu, &mut gpu, &mut memory).unwrap();
+
+        assert_eq!(cpu.registers().a, 0x12);
+        assert_eq!(cpu.registers().program_counter, 0x0150);
+        assert_eq!(memory.read_byte(0xC000), 0x42);
+        assert_eq!(memory.read_byte(0xFF80), 0x99);
+        assert_eq!(save_state(&cpu, &gpu, &memory), state);
+    }
+
+    #[test]
+    fn other_versions_are_rejected_before_anything_changes() {
+        let (mut cpu, mut gpu, mut memory) = machine();
+        let mut state = save_state(&cpu, &gpu, &memory);
+        state[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        cpu.registers_mut().a = 0x34;
+        let before = save_state(&cpu, &gpu, &memory);
+
+        let result = load_state(&state, &mut cpu, &mut gpu, &mut memory);
+        assert_eq!(result, Err(StateError::UnsupportedVersion(VERSION + 1)));
+        assert_eq!(save_state(&cpu, &gpu, &memory), before);
+    }
+
+    #[test]
+    fn corrupt_section_rolls_the_machine_back() {
+        let (mut cpu, mut gpu, mut memory) = machine();
+        cpu.registers_mut().a = 0x12;
+        // The CPU and PPU sections load fine; memory, read last, is cut short
+        let state = rebuild(&save_state(&cpu, &gpu, &memory), |tag, payload| {
+            if tag == MEMORY {
+                payload[..payload.len() / 2].to_vec()
+            } else {
+                payload.to_vec()
+            }
+        });
+
+        cpu.registers_mut().a = 0x34;
+        memory.write_byte(0xC000, 0x42);
+        let before = save_state(&cpu, &gpu, &memory);
+        let result = load_state(&state, &mut cpu, &mut gpu, &mut memory);
+        assert_eq!(result, Err(StateError::Truncated));
+        assert_eq!(cpu.registers().a, 0x34);
+        assert_eq!(save_state(&cpu, &gpu, &memory), before);
+    }
+}
@@ -0,0 +1,503 @@
+// Game Boy serial port — SB/SC (0xFF01–0xFF02).
+//
+// A transfer shifts the 8 bits of SB out while shifting 8 bits in from the
+// link partner. With the internal clock (SC bit 0 = 1) this side drives the
+// 8192 Hz shift clock, so a byte takes 4096 T-cycles; in CGB mode SC bit 1
+// selects a 262144 Hz clock instead, 32 times as fast. With the external clock
+// the transfer only completes when the partner clocks it. Either way, the end
+// of a transfer clears SC bit 7 and raises the serial interrupt (IF bit 3).
+//
+// What sits on the other end of the cable is a `SerialDevice`.
+
+use crate::savestate::{StateError, StateReader, StateWriter};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// T-cycles needed to shift one byte at the internal 8192 Hz clock.
+const TRANSFER_CYCLES: u32 = 4096;
+/// T-cycles per byte at the CGB's fast internal clock (SC bit 1).
+const FAST_TRANSFER_CYCLES: u32 = TRANSFER_CYCLES / 32;
+/// How often a transfer waiting on the partner polls the device, in T-cycles.
+const POLL_CYCLES: u32 = 512;
+
+/// The far end of the link cable.
+pub trait SerialDevice {
+    /// This Game Boy drives the clock: send `byte` and return the byte the
+    /// partner shifted back, or None if the reply has not arrived yet.
+    fn transfer(&mut self, byte: u8) -> Option<u8>;
+
+    /// The reply to a `transfer` that returned None, once it has arrived.
+    /// Devices that always answer at once never see this called.
+    fn poll_reply(&mut self) -> Option<u8> {
+        Some(0xFF)
+    }
+
+    /// This Game Boy waits on an external clock. If the partner has clocked a
+    /// transfer, hand it `byte` (our SB) and return the byte it sent.
+    fn poll_external(&mut self, byte: u8) -> Option<u8> {
+        let _ = byte;
+        None
+    }
+}
+
+/// No cable attached: the input line floats high, so every transfer reads 0xFF.
+pub struct Disconnected;
+
+impl SerialDevice for Disconnected {
+    fn transfer(&mut self, _byte: u8) -> Option<u8> {
+        Some(0xFF)
+    }
+}
+
+/// Records every byte sent by the Game Boy, optionally echoing it to stdout.
+/// Test ROMs (e.g. Blargg's) print their results this way.
+pub struct CaptureSink {
+    output: Rc<RefCell<Vec<u8>>>,
+    echo: bool,
+}
+
+impl CaptureSink {
+    pub fn new(echo: bool) -> Self {
+        CaptureSink {
+            output: Rc::new(RefCell::new(Vec::new())),
+            echo,
+        }
+    }
+
+    /// Shared handle to the captured bytes; stays valid after the sink is
+    /// moved into `Memory`.
+    pub fn output(&self) -> Rc<RefCell<Vec<u8>>> {
+        Rc::clone(&self.output)
+    }
+}
+
+impl SerialDevice for CaptureSink {
+    fn transfer(&mut self, byte: u8) -> Option<u8> {
+        self.output.borrow_mut().push(byte);
+        if self.echo {
+            use std::io::Write;
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(&[byte]);
+            let _ = stdout.flush();
+        }
+        Some(0xFF)
+    }
+}
+
+/// SO wired straight back to SI: every byte sent is received unchanged.
+pub struct Loopback;
+
+impl SerialDevice for Loopback {
+    fn transfer(&mut self, byte: u8) -> Option<u8> {
+        Some(byte)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TCP link cable (native only)
+// ---------------------------------------------------------------------------
+
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(unused_imports)] // used by the native frontend
+pub use tcp::TcpLink;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod tcp {
+    use super::SerialDevice;
+    use std::io::{self, Read, Write};
+    use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+    use std::time::{Duration, Instant};
+
+    // Wire format: every message is 3 bytes [kind, sequence, data].
+    // The clock master sends DATA and the partner answers with REPLY carrying
+    // the same sequence number, so a late reply can be told apart from a
+    // fresh one.
+    const DATA: u8 = 0x01;
+    const REPLY: u8 = 0x02;
+
+    /// How long the clock master waits for the partner before giving up and
+    /// reading 0xFF, as if the cable were unplugged. The emulator keeps
+    /// running meanwhile; the transfer just stays pending.
+    const REPLY_TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Link cable to another emulator process over a TCP socket.
+    pub struct TcpLink {
+        stream: TcpStream,
+        sequence: u8,
+        // Sequence number and send time of the DATA message awaiting its REPLY
+        awaiting: Option<(u8, Instant)>,
+        // DATA message received while we were waiting for a REPLY
+        pending: Option<(u8, u8)>,
+        // Bytes of messages not yet complete; a read can end mid-message
+        received: Vec<u8>,
+    }
+
+    impl TcpLink {
+        /// Wait for the partner to connect on `addr`.
+        pub fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+            let listener = TcpListener::bind(addr)?;
+            let (stream, _) = listener.accept()?;
+            Self::from_stream(stream)
+        }
+
+        /// Connect to a partner listening on `addr`.
+        pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+            Self::from_stream(TcpStream::connect(addr)?)
+        }
+
+        fn from_stream(stream: TcpStream) -> io::Result<Self> {
+            stream.set_nodelay(true)?;
+            stream.set_nonblocking(true)?;
+            Ok(TcpLink {
+                stream,
+                sequence: 0,
+                awaiting: None,
+                pending: None,
+                received: Vec::new(),
+            })
+        }
+
+        /// Write one message. Reads never block, but a message is never left
+        /// half-written.
+        fn send(&mut self, kind: u8, sequence: u8, data: u8) -> io::Result<()> {
+            self.stream.set_nonblocking(false)?;
+            let sent = self.stream.write_all(&[kind, sequence, data]);
+            self.stream.set_nonblocking(true)?;
+            sent
+        }
+
+        /// Read one message if a whole one has arrived, without blocking.
+        /// Part of a message is kept for the next call.
+        fn receive(&mut self) -> io::Result<Option<[u8; 3]>> {
+            while self.received.len() < 3 {
+                let mut buffer = [0u8; 64];
+                match self.stream.read(&mut buffer) {
+                    Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    Ok(count) => self.received.extend_from_slice(&buffer[..count]),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                    Err(e) => return Err(e),
+                }
+            }
+            let msg = [self.received[0], self.received[1], self.received[2]];
+            self.received.drain(..3);
+            Ok(Some(msg))
+        }
+    }
+
+    impl SerialDevice for TcpLink {
+        fn transfer(&mut self, byte: u8) -> Option<u8> {
+            self.sequence = self.sequence.wrapping_add(1);
+            if self.send(DATA, self.sequence, byte).is_err() {
+                return Some(0xFF);
+            }
+            self.awaiting = Some((self.sequence, Instant::now()));
+            self.poll_reply()
+        }
+
+        fn poll_reply(&mut self) -> Option<u8> {
+            let Some((sequence, sent)) = self.awaiting else {
+                return Some(0xFF);
+            };
+            let reply = loop {
+                match self.receive() {
+                    Ok(Some([REPLY, seq, data])) if seq == sequence => break data,
+                    Ok(Some([DATA, seq, data])) => self.pending = Some((seq, data)),
+                    // Stale reply to a transfer that already timed out
+                    Ok(Some(_)) => {}
+                    Ok(None) if sent.elapsed() < REPLY_TIMEOUT => return None,
+                    Ok(None) | Err(_) => break 0xFF,
+                }
+            };
+            self.awaiting = None;
+            Some(reply)
+        }
+
+        fn poll_external(&mut self, byte: u8) -> Option<u8> {
+            let (sequence, data) = match self.pending.take() {
+                Some(message) => message,
+                None => match self.receive() {
+                    Ok(Some([DATA, seq, data])) => (seq, data),
+                    _ => return None,
+                },
+            };
+            let _ = self.send(REPLY, sequence, byte);
+            Some(data)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::net::TcpListener;
+
+        #[test]
+        fn message_split_across_writes_is_reassembled() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let mut partner = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            partner.set_nodelay(true).unwrap();
+            let mut link = TcpLink::from_stream(listener.accept().unwrap().0).unwrap();
+
+            partner.write_all(&[DATA, 7]).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+            assert_eq!(link.poll_external(0x55), None);
+
+            partner.write_all(&[0x42]).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+            assert_eq!(link.poll_external(0x55), Some(0x42));
+            let mut reply = [0u8; 3];
+            partner.read_exact(&mut reply).unwrap();
+            assert_eq!(reply, [REPLY, 7, 0x55]);
+        }
+
+        /// A link with its partner's end of the socket.
+        fn pair() -> (TcpLink, TcpStream) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let partner = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            partner.set_nodelay(true).unwrap();
+            let link = TcpLink::from_stream(listener.accept().unwrap().0).unwrap();
+            (link, partner)
+        }
+
+        #[test]
+        fn master_transfer_returns_before_the_reply_and_completes_on_a_later_poll() {
+            let (mut link, mut partner) = pair();
+            assert_eq!(link.transfer(0x12), None);
+            let mut data = [0u8; 3];
+            partner.read_exact(&mut data).unwrap();
+            assert_eq!(data, [DATA, 1, 0x12]);
+            assert_eq!(link.poll_reply(), None);
+
+            partner.write_all(&[REPLY, 1, 0x34]).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+            assert_eq!(link.poll_reply(), Some(0x34));
+        }
+
+        #[test]
+        fn unanswered_transfer_reads_ff_after_the_timeout() {
+            let (mut link, _partner) = pair();
+            assert_eq!(link.transfer(0x12), None);
+            std::thread::sleep(REPLY_TIMEOUT);
+            assert_eq!(link.poll_reply(), Some(0xFF));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Serial port registers
+// ---------------------------------------------------------------------------
+
+pub struct Serial {
+    sb: u8,
+    sc: u8,
+    // T-cycles until the current transfer completes (internal clock) or the
+    // device is polled again (external clock, or a reply still on its way)
+    countdown: u32,
+    // The byte has gone out to the device, which has yet to answer
+    awaiting_reply: bool,
+    device: Box<dyn SerialDevice>,
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serial {
+    pub fn new() -> Self {
+        Serial {
+            sb: 0,
+            sc: 0,
+            countdown: 0,
+            awaiting_reply: false,
+            device: Box::new(Disconnected),
+        }
+    }
+
+    pub fn set_device(&mut self, device: Box<dyn SerialDevice>) {
+        self.device = device;
+    }
+
+    /// The attached device is part of the host setup, not the machine, so
+    /// only the registers and the transfer in progress are saved. A transfer
+    /// loaded while waiting on a reply sends its byte again.
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.u8(self.sb);
+        w.u8(self.sc);
+        w.u32(self.countdown);
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.sb = r.u8()?;
+        self.sc = r.u8()? & 0x83;
+        self.countdown = r.u32()?;
+        self.awaiting_reply = false;
+        Ok(())
+    }
+
+    /// SC's unused bits read 1; in CGB mode bit 1 is the clock speed.
+    pub fn read(&self, addr: u16, cgb: bool) -> u8 {
+        match addr {
+            0xFF01 => self.sb,
+            0xFF02 if cgb => 0x7C | self.sc,
+            0xFF02 => 0x7E | self.sc,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8, cgb: bool) {
+        match addr {
+            0xFF01 => self.sb = val,
+            0xFF02 => {
+                self.sc = val & if cgb { 0x83 } else { 0x81 };
+                self.awaiting_reply = false;
+                self.countdown = match self.sc & 0x03 {
+                    0x01 => TRANSFER_CYCLES,
+                    0x03 => FAST_TRANSFER_CYCLES,
+                    _ => 0,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Advance by `cycles` T-cycles. Returns true when a transfer completes
+    /// and the serial interrupt should fire.
+    pub fn tick(&mut self, cycles: u32) -> bool {
+        if self.sc & 0x80 == 0 {
+            return false;
+        }
+        if self.countdown > cycles {
+            self.countdown -= cycles;
+            return false;
+        }
+        let received = if self.sc & 0x01 == 0 {
+            self.device.poll_external(self.sb)
+        } else if self.awaiting_reply {
+            self.device.poll_reply()
+        } else {
+            self.awaiting_reply = true;
+            self.device.transfer(self.sb)
+        };
+        let Some(byte) = received else {
+            self.countdown = POLL_CYCLES;
+            return false;
+        };
+        self.sb = byte;
+        self.awaiting_reply = false;
+        self.sc &= 0x7F;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A serial port wired to itself, with `byte` in SB.
+    fn looped(byte: u8) -> Serial {
+        let mut serial = Serial::new();
+        serial.set_device(Box::new(Loopback));
+        serial.write(0xFF01, byte, false);
+        serial
+    }
+
+    /// A partner that answers `0x99` only on its second poll for the reply.
+    struct Slow {
+        polls: u32,
+    }
+
+    impl SerialDevice for Slow {
+        fn transfer(&mut self, _byte: u8) -> Option<u8> {
+            None
+        }
+
+        fn poll_reply(&mut self) -> Option<u8> {
+            self.polls += 1;
+            (self.polls == 2).then_some(0x99)
+        }
+    }
+
+    #[test]
+    fn internal_clock_transfer_completes_after_transfer_cycles() {
+        let mut serial = looped(0x5A);
+        serial.write(0xFF02, 0x81, false);
+        assert!(!serial.tick(TRANSFER_CYCLES - 1));
+        assert_eq!(serial.read(0xFF02, false), 0xFF);
+        assert!(serial.tick(1));
+        assert_eq!(serial.read(0xFF02, false), 0x7F);
+        assert_eq!(serial.read(0xFF01, false), 0x5A);
+        assert!(!serial.tick(TRANSFER_CYCLES));
+    }
+
+    #[test]
+    fn external_clock_without_a_partner_never_completes() {
+        let mut serial = Serial::new();
+        serial.write(0xFF02, 0x80, false);
+        for _ in 0..1000 {
+            assert!(!serial.tick(TRANSFER_CYCLES));
+        }
+        assert_eq!(serial.read(0xFF02, false), 0xFE);
+    }
+
+    #[test]
+    fn capture_sink_records_each_byte_and_reads_ff() {
+        let sink = CaptureSink::new(false);
+        let output = sink.output();
+        let mut serial = Serial::new();
+        serial.set_device(Box::new(sink));
+        for &byte in b"OK" {
+            serial.write(0xFF01, byte, false);
+            serial.write(0xFF02, 0x81, false);
+            assert!(serial.tick(TRANSFER_CYCLES));
+            assert_eq!(serial.read(0xFF01, false), 0xFF);
+        }
+        assert_eq!(*output.borrow(), b"OK");
+    }
+
+    #[test]
+    fn late_reply_keeps_the_transfer_pending() {
+        let mut serial = Serial::new();
+        serial.set_device(Box::new(Slow { polls: 0 }));
+        serial.write(0xFF02, 0x81, false);
+        assert!(!serial.tick(TRANSFER_CYCLES));
+        assert!(!serial.tick(POLL_CYCLES));
+        assert_eq!(serial.read(0xFF02, false), 0xFF);
+        assert!(!serial.tick(POLL_CYCLES - 1));
+        assert!(serial.tick(1));
+        assert_eq!(serial.read(0xFF01, false), 0x99);
+        assert_eq!(serial.read(0xFF02, false), 0x7F);
+    }
+
+    #[test]
+    fn cgb_fast_clock_finishes_in_a_thirty_second_of_the_time() {
+        let mut serial = looped(0x5A);
+        serial.write(0xFF02, 0x83, true);
+        assert_eq!(serial.read(0xFF02, true), 0xFF);
+        assert!(!serial.tick(FAST_TRANSFER_CYCLES - 1));
+        assert!(serial.tick(1));
+        assert_eq!(serial.read(0xFF01, true), 0x5A);
+        assert_eq!(serial.read(0xFF02, true), 0x7F);
+    }
+
+    #[test]
+    fn cgb_mode_reads_the_clock_speed_bit_back() {
+        let mut serial = looped(0);
+        serial.write(0xFF02, 0x81, true);
+        assert_eq!(serial.read(0xFF02, true), 0xFD);
+        serial.write(0xFF02, 0x02, true);
+        assert_eq!(serial.read(0xFF02, true), 0x7E);
+    }
+
+    #[test]
+    fn dmg_ignores_the_clock_speed_bit() {
+        let mut serial = looped(0);
+        serial.write(0xFF02, 0x83, false);
+        assert_eq!(serial.read(0xFF02, false), 0xFF);
+        assert!(!serial.tick(FAST_TRANSFER_CYCLES));
+        assert!(!serial.tick(TRANSFER_CYCLES - FAST_TRANSFER_CYCLES - 1));
+        assert!(serial.tick(1));
+        serial.write(0xFF02, 0x02, false);
+        assert_eq!(serial.read(0xFF02, false), 0x7E);
+    }
+}
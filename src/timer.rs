@@ -0,0 +1,241 @@
+// Game Boy timer — DIV/TIMA/TMA/TAC (0xFF04–0xFF07).
+//
+// DIV is the upper byte of a free-running 16-bit counter that advances every
+// T-cycle. TIMA is not clocked directly: it increments on the falling edge of
+// one counter bit (selected by TAC) ANDed with the TAC enable bit. Because of
+// that, resetting DIV or rewriting TAC can itself produce a falling edge and
+// bump TIMA — games and test ROMs rely on these quirks.
+//
+// When TIMA overflows it reads 0x00 for one M-cycle before being reloaded
+// from TMA and raising the timer interrupt (IF bit 2).
+
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+/// Counter bit watched for each TAC clock select value (00, 01, 10, 11).
+/// 4096 Hz, 262144 Hz, 65536 Hz and 16384 Hz respectively.
+const TAC_BITS: [u16; 4] = [9, 3, 5, 7];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimaState {
+    Running,
+    // TIMA overflowed during the last M-cycle and currently reads 0x00.
+    // A write to TIMA in this window cancels the reload.
+    Overflow,
+    // TIMA was reloaded from TMA during the last M-cycle. Writes to TIMA are
+    // ignored and writes to TMA also land in TIMA.
+    Reloaded,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timer {
+    counter: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    state: TimaState,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Timer {
+            counter: 0,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            state: TimaState::Running,
+        }
+    }
+
+    /// The signal whose falling edge clocks TIMA.
+    fn input(&self) -> bool {
+        let bit = TAC_BITS[(self.tac & 0x03) as usize];
+        self.tac & 0x04 != 0 && self.counter & (1 << bit) != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (value, overflow) = self.tima.overflowing_add(1);
+        self.tima = value;
+        if overflow {
+            self.state = TimaState::Overflow;
+        }
+    }
+
+    /// Set the internal counter DIV is the upper byte of, e.g. to its post-boot value.
+    pub fn set_counter(&mut self, counter: u16) {
+        self.counter = counter;
+    }
+
+    /// Advance by `cycles` T-cycles. Returns true when the timer interrupt fires.
+    pub fn tick(&mut self, cycles: u32) -> bool {
+        let mut interrupt = false;
+        for _ in 0..(cycles / 4) {
+            match self.state {
+                TimaState::Overflow => {
+                    self.tima = self.tma;
+                    self.state = TimaState::Reloaded;
+                    interrupt = true;
+                }
+                TimaState::Reloaded => self.state = TimaState::Running,
+                TimaState::Running => {}
+            }
+            let before = self.input();
+            self.counter = self.counter.wrapping_add(4);
+            if before && !self.input() {
+                self.increment_tima();
+            }
+        }
+        interrupt
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF04 => (self.counter >> 8) as u8,
+            0xFF05 => self.tima,
+            0xFF06 => self.tma,
+            0xFF07 => 0xF8 | self.tac,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0xFF04 => {
+                // Any write resets the whole counter, which can drop the
+                // selected bit from 1 to 0 and clock TIMA once.
+                let before = self.input();
+                self.counter = 0;
+                if before {
+                    self.increment_tima();
+                }
+            }
+            0xFF05 => match self.state {
+                TimaState::Overflow => {
+                    self.tima = val;
+                    self.state = TimaState::Running;
+                }
+                TimaState::Reloaded => {}
+                TimaState::Running => self.tima = val,
+            },
+            0xFF06 => {
+                self.tma = val;
+                if self.state == TimaState::Reloaded {
+                    self.tima = val;
+                }
+            }
+            0xFF07 => {
+                // Disabling the timer or switching to a bit that is currently
+                // low is seen as a falling edge on DMG.
+                let before = self.input();
+                self.tac = val & 0x07;
+                if before && !self.input() {
+                    self.increment_tima();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn save_state(&self, w: &mut StateWriter) {
+        w.u16(self.counter);
+        w.u8(self.tima);
+        w.u8(self.tma);
+        w.u8(self.tac);
+        w.u8(match self.state {
+            TimaState::Running => 0,
+            TimaState::Overflow => 1,
+            TimaState::Reloaded => 2,
+        });
+    }
+
+    pub fn load_state(&mut self, r: &mut StateReader) -> Result<(), StateError> {
+        self.counter = r.u16()?;
+        self.tima = r.u8()?;
+        self.tma = r.u8()?;
+        self.tac = r.u8()? & 0x07;
+        self.state = match r.u8()? {
+            0 => TimaState::Running,
+            1 => TimaState::Overflow,
+            2 => TimaState::Reloaded,
+            _ => return Err(StateError::Invalid("timer state")),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A timer clocking TIMA from counter bit 3 (every 16 T-cycles).
+    fn fast_timer() -> Timer {
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05);
+        timer
+    }
+
+    #[test]
+    fn div_reset_clocks_tima_only_when_the_selected_bit_is_high() {
+        let mut timer = fast_timer();
+        timer.tick(4); // counter 4: bit 3 low
+        timer.write(0xFF04, 0x00);
+        assert_eq!(timer.read(0xFF05), 0);
+
+        timer.tick(8); // counter 8: bit 3 high
+        timer.write(0xFF04, 0x00);
+        assert_eq!(timer.read(0xFF05), 1);
+        assert_eq!(timer.read(0xFF04), 0);
+    }
+
+    #[test]
+    fn tac_changes_that_drop_the_input_clock_tima() {
+        let mut timer = fast_timer();
+        timer.tick(8);
+        // Counter bit 5 is low, so switching to it is a falling edge
+        timer.write(0xFF07, 0x06);
+        assert_eq!(timer.read(0xFF05), 1);
+
+        let mut timer = fast_timer();
+        timer.tick(8);
+        timer.write(0xFF07, 0x01); // disabled
+        assert_eq!(timer.read(0xFF05), 1);
+
+        // With the input already low, nothing happens
+        let mut timer = fast_timer();
+        timer.write(0xFF07, 0x00);
+        assert_eq!(timer.read(0xFF05), 0);
+    }
+
+    #[test]
+    fn overflow_reads_zero_for_an_m_cycle_then_reloads_from_tma() {
+        let mut timer = fast_timer();
+        timer.write(0xFF05, 0xFF);
+        timer.write(0xFF06, 0x42);
+        assert!(!timer.tick(16));
+        assert_eq!(timer.read(0xFF05), 0x00);
+        assert!(timer.tick(4));
+        assert_eq!(timer.read(0xFF05), 0x42);
+
+        // In the reload M-cycle TIMA writes are lost and TMA writes land in TIMA
+        timer.write(0xFF05, 0x10);
+        assert_eq!(timer.read(0xFF05), 0x42);
+        timer.write(0xFF06, 0x33);
+        assert_eq!(timer.read(0xFF05), 0x33);
+    }
+
+    #[test]
+    fn tima_write_during_the_overflow_cycle_cancels_the_reload() {
+        let mut timer = fast_timer();
+        timer.write(0xFF05, 0xFF);
+        timer.write(0xFF06, 0x42);
+        timer.tick(16);
+        timer.write(0xFF05, 0x10);
+        assert!(!timer.tick(4));
+        assert_eq!(timer.read(0xFF05), 0x10);
+    }
+}
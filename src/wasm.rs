@@ -0,0 +1,355 @@
+// WASM frontend bindings — exports the emulator to JavaScript via wasm-bindgen.
+// A thin wrapper over `GameBoy` that converts frames and audio into the
+// formats the web page wants and adds the debug views (instruction log,
+// tileset, memory map) and the debugger.
+
+use wasm_bindgen::prelude::*;
+
+use crate::boot::Boot;
+use crate::debugger::{self, Condition, Debugger, StepMode, Watchpoint};
+use crate::gameboy::{GameBoy, CYCLES_PER_FRAME};
+use crate::gpu::Renderer;
+use crate::joypad::Button;
+use std::collections::VecDeque;
+
+const LOG_CAPACITY: usize = 64;
+
+// Game Boy screen dimensions
+const SCREEN_WIDTH: usize = 160;
+const SCREEN_HEIGHT: usize = 144;
+
+const TILESET_WIDTH: usize = 128; // 16 tiles across
+const TILESET_HEIGHT: usize = 192; // 24 tiles down
+
+const MEMORY_WIDTH: usize = 256;
+const MEMORY_HEIGHT: usize = 256;
+
+/// The main Emulator struct exposed to JavaScript.
+#[wasm_bindgen]
+pub struct Emulator {
+    gb: GameBoy,
+    // Instruction log: most-recent first, capped at LOG_CAPACITY entries.
+    // Only recorded while enabled, since formatting every instruction is slow.
+    instruction_log: VecDeque<String>,
+    instruction_log_enabled: bool,
+    // Audio: stereo f32 samples accumulated during tick(), drained by JS each frame
+    audio_buf: Vec<f32>,
+    // Created by the first debugger call; until then tick() runs at full speed
+    debugger: Option<Debugger>,
+    // Why the debugger last stopped, until `take_break`
+    last_break: Option<String>,
+}
+
+#[wasm_bindgen]
+impl Emulator {
+    /// Creates a new Emulator instance with the ROM bytes provided by JavaScript.
+    /// Starts at 0x0100 without a boot ROM, in the CGB post-boot state for
+    /// cartridges with CGB support and the DMG one otherwise.
+    /// Throws if the cartridge type is not supported.
+    #[wasm_bindgen(constructor)]
+    pub fn new(rom_data: Vec<u8>) -> Result<Emulator, JsValue> {
+        Emulator::create(rom_data, None)
+    }
+
+    /// Like `new`, but runs the given boot ROM (logo scroll and header check) first.
+    /// Throws if the cartridge type is not supported or the boot ROM has the wrong size.
+    pub fn with_bios(rom_data: Vec<u8>, bios: Vec<u8>) -> Result<Emulator, JsValue> {
+        Emulator::create(rom_data, Some(Boot::Bios(bios)))
+    }
+
+    fn create(rom_data: Vec<u8>, boot: Option<Boot>) -> Result<Emulator, JsValue> {
+        #[cfg(feature = "console_error_panic_hook")]
+        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+        let gb = GameBoy::new(rom_data, boot).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(Emulator {
+            gb,
+            instruction_log: VecDeque::with_capacity(LOG_CAPACITY),
+            instruction_log_enabled: false,
+            audio_buf: Vec::with_capacity(4096),
+            debugger: None,
+            last_break: None,
+        })
+    }
+
+    /// Executes enough CPU and GPU cycles to produce one frame.
+    /// Call this from a requestAnimationFrame loop in JavaScript.
+    /// While the debugger is paused, does nothing.
+    pub fn tick(&mut self) {
+        let log = &mut self.instruction_log;
+        match (&mut self.debugger, self.instruction_log_enabled) {
+            (Some(debugger), logging) => {
+                let stop = if logging {
+                    debugger.run_frame_logged(&mut self.gb, |instr| push_log(log, instr))
+                } else {
+                    debugger.run_frame(&mut self.gb)
+                };
+                if let Some(stop) = stop {
+                    self.last_break = Some(stop.to_string());
+                }
+            }
+            (None, true) => {
+                let mut cycles = 0;
+                while cycles < CYCLES_PER_FRAME {
+                    let (step, instr) = self.gb.step_instruction_logged();
+                    push_log(log, instr);
+                    if step.frame_completed {
+                        break;
+                    }
+                    cycles += step.cycles;
+                }
+            }
+            (None, false) => {
+                self.gb.run_frame();
+            }
+        }
+        // Accumulate APU samples (stereo f32, interleaved L/R)
+        for sample in self.gb.take_samples() {
+            self.audio_buf.push(sample as f32 / 32768.0);
+        }
+    }
+
+    /// Drains and returns accumulated stereo audio samples as f32 in [-1, 1].
+    /// Interleaved: [L0, R0, L1, R1, ...]. Call this once per frame after tick().
+    pub fn get_audio_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.audio_buf)
+    }
+
+    /// Returns the current frame as an RGBA byte vector (160×144×4 bytes).
+    pub fn get_framebuffer(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+        for pixel in self.gb.framebuffer().0.iter() {
+            buf.extend_from_slice(&[pixel.r, pixel.g, pixel.b, 255]);
+        }
+        buf
+    }
+
+    /// Starts or stops recording the instruction log. Turning it off clears the log.
+    pub fn set_instruction_log_enabled(&mut self, enabled: bool) {
+        self.instruction_log_enabled = enabled;
+        if !enabled {
+            self.instruction_log.clear();
+        }
+    }
+
+    /// Returns the instruction log as a newline-separated string (most-recent first).
+    pub fn get_instruction_log(&self) -> String {
+        self.instruction_log
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Runs a debugger console command (`help` lists them) and returns its
+    /// output. Throws on an unknown command or a bad argument.
+    pub fn debug_command(&mut self, line: String) -> Result<String, JsValue> {
+        self.debugger
+            .get_or_insert_with(Debugger::new)
+            .command(&mut self.gb, &line)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Breaks when PC reaches `addr`, and the condition (e.g. "a == 0x42")
+    /// holds if one is given. Returns the breakpoint's number.
+    pub fn add_breakpoint(&mut self, addr: u16, condition: Option<String>) -> Result<u32, JsValue> {
+        let condition = condition
+            .map(|c| Condition::parse(&c))
+            .transpose()
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(self.debugger().add_breakpoint(addr, condition))
+    }
+
+    /// Breaks on accesses to `start..=end`; `kinds` is any of "r", "w" and
+    /// "x" together, e.g. "rw". Returns the watchpoint's number.
+    pub fn add_watchpoint(&mut self, start: u16, end: u16, kinds: String) -> Result<u32, JsValue> {
+        let watchpoint =
+            Watchpoint::new(start, end, &kinds).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(self.debugger().add_watchpoint(watchpoint))
+    }
+
+    /// Breaks whenever a register condition such as "hl >= 0xC000" becomes
+    /// true. Returns the condition's number.
+    pub fn add_condition(&mut self, condition: String) -> Result<u32, JsValue> {
+        let condition =
+            Condition::parse(&condition).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(self.debugger().add_condition(condition))
+    }
+
+    /// Removes a breakpoint, watchpoint or condition by number.
+    pub fn remove_breakpoint(&mut self, id: u32) -> Result<(), JsValue> {
+        self.debugger()
+            .remove(id)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Lists breakpoints, watchpoints and conditions, one per line.
+    pub fn list_breakpoints(&mut self) -> String {
+        self.debugger().list()
+    }
+
+    /// Breaks whenever an interrupt is dispatched, stopping at its vector.
+    pub fn set_break_on_interrupt(&mut self, enabled: bool) {
+        self.debugger().set_break_on_interrupt(enabled);
+    }
+
+    /// Breaks on unimplemented opcodes (on by default).
+    pub fn set_break_on_unimplemented(&mut self, enabled: bool) {
+        self.debugger().set_break_on_unimplemented(enabled);
+    }
+
+    /// Runs one instruction on the next tick(), following calls.
+    pub fn step_in(&mut self) {
+        self.step(StepMode::In);
+    }
+
+    /// Runs one instruction, or a whole CALL or RST, over the next tick()s.
+    pub fn step_over(&mut self) {
+        self.step(StepMode::Over);
+    }
+
+    /// Runs until the current function returns.
+    pub fn step_out(&mut self) {
+        self.step(StepMode::Out);
+    }
+
+    fn step(&mut self, mode: StepMode) {
+        self.debugger
+            .get_or_insert_with(Debugger::new)
+            .step(&self.gb, mode);
+    }
+
+    /// Lets tick() run until something breaks.
+    pub fn resume(&mut self) {
+        self.debugger().resume();
+    }
+
+    /// Stops before the next instruction.
+    pub fn pause(&mut self) {
+        self.debugger().pause();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.debugger.as_ref().is_some_and(Debugger::paused)
+    }
+
+    /// Why the debugger stopped, e.g. "breakpoint 1 at 0x0150", once per stop.
+    pub fn take_break(&mut self) -> Option<String> {
+        self.last_break.take()
+    }
+
+    /// Registers, flags and the next instruction, as shown by the `regs` command.
+    pub fn registers(&self) -> String {
+        debugger::registers(&self.gb)
+    }
+
+    fn debugger(&mut self) -> &mut Debugger {
+        self.debugger.get_or_insert_with(Debugger::new)
+    }
+
+    /// Switches the PPU renderer: "scanline" (fast, the default) or "fifo"
+    /// (dot-accurate, for mid-scanline effects). Throws on an unknown name.
+    pub fn set_renderer(&mut self, name: String) -> Result<(), JsValue> {
+        let renderer = Renderer::from_name(&name)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown renderer '{}'", name)))?;
+        self.gb.set_renderer(renderer);
+        Ok(())
+    }
+
+    /// Starts or stops recording CPU accesses to VRAM and OAM that the PPU
+    /// blocked. Turning it off clears the record.
+    pub fn set_strict(&mut self, enabled: bool) {
+        self.gb.set_strict(enabled);
+    }
+
+    /// Returns the accesses blocked since the last call as a newline-separated
+    /// string, oldest first, e.g. "PC=0x0213: write 0x01 to 0x9800 blocked in mode 3".
+    pub fn take_blocked_accesses(&mut self) -> String {
+        self.gb
+            .take_blocked()
+            .iter()
+            .map(|access| access.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the VRAM tileset as an RGBA byte vector (128×192 px, 384 tiles).
+    pub fn get_tileset(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; TILESET_WIDTH * TILESET_HEIGHT * 4];
+        self.gb.memory().generate_tileset_rgba(&mut buf);
+        buf
+    }
+
+    /// Returns the full 64KB memory map as an RGBA byte vector (256×256 px).
+    pub fn get_memory_map(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; MEMORY_WIDTH * MEMORY_HEIGHT * 4];
+        self.gb.memory().generate_memory_rgba(&mut buf);
+        buf
+    }
+
+    /// Returns battery-backed cartridge RAM in `.sav` format (compatible with
+    /// other emulators), or undefined if the cartridge has no battery.
+    pub fn export_save(&self) -> Option<Vec<u8>> {
+        self.gb.save_data()
+    }
+
+    /// Restores battery-backed cartridge RAM from `.sav` bytes.
+    pub fn import_save(&mut self, bytes: Vec<u8>) {
+        self.gb.load_save_data(&bytes);
+    }
+
+    /// Snapshots the whole machine (CPU, PPU, APU, memory and cartridge state).
+    pub fn save_state(&self) -> Vec<u8> {
+        self.gb.save_state()
+    }
+
+    /// Restores a snapshot from `save_state`. Throws if it is corrupt, was
+    /// written by another format version, or belongs to a different ROM.
+    pub fn load_state(&mut self, bytes: Vec<u8>) -> Result<(), JsValue> {
+        self.gb
+            .load_state(&bytes)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Whether the cartridge's rumble motor is on, e.g. to drive navigator.vibrate().
+    pub fn rumble(&self) -> bool {
+        self.gb.rumble()
+    }
+
+    /// Called by JavaScript on keydown. key_code is the browser KeyboardEvent.code value.
+    pub fn key_down(&mut self, key_code: String) {
+        if let Some(button) = key_button(&key_code) {
+            self.gb.set_button(button, true);
+        }
+    }
+
+    /// Called by JavaScript on keyup.
+    pub fn key_up(&mut self, key_code: String) {
+        if let Some(button) = key_button(&key_code) {
+            self.gb.set_button(button, false);
+        }
+    }
+}
+
+/// Add an entry to the front of the instruction log, dropping the oldest if full.
+fn push_log(log: &mut VecDeque<String>, instr: String) {
+    if log.len() == LOG_CAPACITY {
+        log.pop_back();
+    }
+    log.push_front(instr);
+}
+
+/// Game Boy button for a browser KeyboardEvent.code value.
+fn key_button(key_code: &str) -> Option<Button> {
+    let button = match key_code {
+        "ArrowRight" => Button::Right,
+        "ArrowLeft" => Button::Left,
+        "ArrowUp" => Button::Up,
+        "ArrowDown" => Button::Down,
+        "KeyZ" => Button::A,
+        "KeyX" => Button::B,
+        "Enter" => Button::Start,
+        "Backspace" | "ShiftLeft" => Button::Select,
+        _ => return None,
+    };
+    Some(button)
+}
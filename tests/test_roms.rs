@@ -0,0 +1,340 @@
+// Test ROM harness — runs community test ROMs through the core, without SDL.
+//
+// The ROMs are not distributed with the repo. Put them under `test-roms/`
+// (or point `TEST_ROMS_DIR` elsewhere) using the layout of the upstream
+// releases:
+//
+//   test-roms/blargg/cpu_instrs/cpu_instrs.gb
+//   test-roms/blargg/instr_timing/instr_timing.gb
+//   test-roms/blargg/mem_timing/mem_timing.gb
+//   test-roms/blargg/dmg_sound/dmg_sound.gb
+//   test-roms/mooneye/acceptance/**/*.gb
+//   test-roms/screens/**/*.gb    with a `<name>.hash` file next to each ROM
+//
+// Any ROM that is missing is skipped with a note on stderr. The ROMs take
+// tens of emulated seconds each, so run the suite with `cargo test --release`.
+//
+// Results are detected the way each suite reports them:
+//   - Blargg ROMs print "Passed" or "Failed" over the serial port, and also
+//     write a status byte and text to cartridge RAM at 0xA000.
+//   - Mooneye ROMs execute `LD B,B` when done, with the Fibonacci numbers
+//     3, 5, 8, 13, 21, 34 in B, C, D, E, H, L on success.
+//   - Anything else can be checked against a known-good screen hash, as
+//     printed by the headless runner.
+
+use emulator::boot::{Boot, Model};
+use emulator::gameboy::CYCLES_PER_FRAME;
+use emulator::serial::CaptureSink;
+use emulator::GameBoy;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// LD B,B — Mooneye's "test finished" breakpoint
+const MOONEYE_BREAKPOINT: u8 = 0x40;
+const MOONEYE_PASS: [u8; 6] = [3, 5, 8, 13, 21, 34];
+// Blargg's cartridge RAM signature at 0xA001–0xA003 once results are valid
+const BLARGG_SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+// Status byte at 0xA000 while the test is still running
+const BLARGG_RUNNING: u8 = 0x80;
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Pass,
+    Fail(String),
+}
+
+fn roms_dir() -> PathBuf {
+    match std::env::var_os("TEST_ROMS_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("test-roms"),
+    }
+}
+
+/// Path to a test ROM, or None (after noting the skip) when it isn't present.
+fn find_rom(relative: &str) -> Option<PathBuf> {
+    let path = roms_dir().join(relative);
+    if path.is_file() {
+        Some(path)
+    } else {
+        eprintln!("skipping: {} not found", path.display());
+        None
+    }
+}
+
+/// Every `.gb` file under `dir`, sorted, recursively.
+fn find_roms(dir: &Path) -> Vec<PathBuf> {
+    let mut roms = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return roms;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            roms.extend(find_roms(&path));
+        } else if path.extension().is_some_and(|ext| ext == "gb") {
+            roms.push(path);
+        }
+    }
+    roms.sort();
+    roms
+}
+
+// ---------------------------------------------------------------------------
+// Machine
+// ---------------------------------------------------------------------------
+
+struct Machine {
+    gb: GameBoy,
+    serial: Rc<RefCell<Vec<u8>>>,
+    // Hash of the last finished frame
+    screen: Option<u64>,
+}
+
+impl Machine {
+    fn load(path: &Path) -> Machine {
+        let rom = std::fs::read(path).unwrap();
+        // Test ROMs expect the DMG post-boot state, and no boot ROM is shipped
+        let mut gb = GameBoy::new(rom, Some(Boot::Skip(Model::Dmg)))
+            .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let sink = CaptureSink::new(false);
+        let serial = sink.output();
+        gb.set_serial_device(Box::new(sink));
+        Machine {
+            gb,
+            serial,
+            screen: None,
+        }
+    }
+
+    /// Run one frame. Returns true if the Mooneye breakpoint was hit, in
+    /// which case the frame is cut short right after it.
+    fn run_frame(&mut self) -> bool {
+        let mut cycles = 0u32;
+        while cycles < CYCLES_PER_FRAME {
+            let pc = self.gb.cpu().registers().program_counter;
+            let at_breakpoint =
+                !self.gb.cpu().halted && self.gb.memory().read_byte(pc) == MOONEYE_BREAKPOINT;
+            let step = self.gb.step_instruction();
+            // Only if LD B,B ran, rather than an interrupt being taken first
+            let breakpoint =
+                at_breakpoint && self.gb.cpu().registers().program_counter == pc.wrapping_add(1);
+            cycles += step.cycles;
+            self.gb.take_samples();
+            if step.frame_completed {
+                self.screen = Some(self.gb.framebuffer().hash());
+                return breakpoint;
+            }
+            if breakpoint {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn serial_text(&self) -> String {
+        String::from_utf8_lossy(&self.serial.borrow()).into_owned()
+    }
+
+    /// Blargg's status byte and message from cartridge RAM, once the
+    /// signature says they are valid and the test has finished.
+    fn blargg_memory_result(&self) -> Option<(u8, String)> {
+        let signature = [
+            self.gb.memory().read_byte(0xA001),
+            self.gb.memory().read_byte(0xA002),
+            self.gb.memory().read_byte(0xA003),
+        ];
+        let status = self.gb.memory().read_byte(0xA000);
+        if signature != BLARGG_SIGNATURE || status == BLARGG_RUNNING {
+            return None;
+        }
+        let text: Vec<u8> = (0xA004..0xBFFF)
+            .map(|addr| self.gb.memory().read_byte(addr))
+            .take_while(|&byte| byte != 0)
+            .collect();
+        Some((status, String::from_utf8_lossy(&text).into_owned()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Result detection
+// ---------------------------------------------------------------------------
+
+/// Run a Blargg ROM until it reports through serial or cartridge RAM.
+fn run_blargg(path: &Path, max_frames: u32) -> Outcome {
+    let mut machine = Machine::load(path);
+    for _ in 0..max_frames {
+        machine.run_frame();
+        let serial = machine.serial_text();
+        if serial.contains("Passed") {
+            return Outcome::Pass;
+        }
+        if serial.contains("Failed") {
+            return Outcome::Fail(serial);
+        }
+        if let Some((status, text)) = machine.blargg_memory_result() {
+            return match status {
+                0 => Outcome::Pass,
+                _ => Outcome::Fail(format!("status {}: {}", status, text)),
+            };
+        }
+    }
+    Outcome::Fail(format!(
+        "no result after {} frames; serial output:\n{}",
+        max_frames,
+        machine.serial_text()
+    ))
+}
+
+/// Run a Mooneye ROM until `LD B,B`, then check the Fibonacci signature.
+fn run_mooneye(path: &Path, max_frames: u32) -> Outcome {
+    let mut machine = Machine::load(path);
+    for _ in 0..max_frames {
+        if machine.run_frame() {
+            let r = machine.gb.cpu().registers();
+            let registers = [r.b, r.c, r.d, r.e, r.h, r.l];
+            return if registers == MOONEYE_PASS {
+                Outcome::Pass
+            } else {
+                Outcome::Fail(format!("registers {:?}", registers))
+            };
+        }
+    }
+    Outcome::Fail(format!("no LD B,B after {} frames", max_frames))
+}
+
+/// Run a ROM until its screen hashes to `expected`.
+fn run_until_screen(path: &Path, expected: u64, max_frames: u32) -> Outcome {
+    let mut machine = Machine::load(path);
+    for _ in 0..max_frames {
+        machine.run_frame();
+        if machine.screen == Some(expected) {
+            return Outcome::Pass;
+        }
+    }
+    let actual = machine.screen.unwrap_or(0);
+    Outcome::Fail(format!(
+        "screen hash {:016x} after {} frames, expected {:016x}",
+        actual, max_frames, expected
+    ))
+}
+
+fn assert_pass(path: &Path, outcome: Outcome) {
+    if let Outcome::Fail(reason) = outcome {
+        panic!("{} failed: {}", path.display(), reason);
+    }
+}
+
+/// Run every ROM and report all failures together.
+fn assert_all_pass(results: Vec<(PathBuf, Outcome)>) {
+    let failures: Vec<String> = results
+        .iter()
+        .filter_map(|(path, outcome)| match outcome {
+            Outcome::Pass => None,
+            Outcome::Fail(reason) => Some(format!("  {}: {}", path.display(), reason)),
+        })
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} ROMs failed:\n{}",
+        failures.len(),
+        results.len(),
+        failures.join("\n")
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Blargg
+// ---------------------------------------------------------------------------
+
+#[test]
+fn blargg_cpu_instrs() {
+    if let Some(path) = find_rom("blargg/cpu_instrs/cpu_instrs.gb") {
+        assert_pass(&path, run_blargg(&path, 4000));
+    }
+}
+
+#[test]
+fn blargg_instr_timing() {
+    if let Some(path) = find_rom("blargg/instr_timing/instr_timing.gb") {
+        assert_pass(&path, run_blargg(&path, 600));
+    }
+}
+
+#[test]
+fn blargg_mem_timing() {
+    if let Some(path) = find_rom("blargg/mem_timing/mem_timing.gb") {
+        assert_pass(&path, run_blargg(&path, 600));
+    }
+}
+
+#[test]
+fn blargg_dmg_sound() {
+    if let Some(path) = find_rom("blargg/dmg_sound/dmg_sound.gb") {
+        assert_pass(&path, run_blargg(&path, 3000));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Mooneye
+// ---------------------------------------------------------------------------
+
+/// Mooneye names end in the models a test is valid for, e.g. `-GS` (DMG,
+/// MGB, SGB), `-dmgABC`, `-cgb` or `-S`. Untagged tests run everywhere.
+fn runs_on_dmg(path: &Path) -> bool {
+    let name = path.file_stem().unwrap().to_string_lossy();
+    match name.rsplit_once('-') {
+        None => true,
+        Some((_, models)) => {
+            models.contains("dmgABC")
+                || (models.chars().all(|c| c.is_ascii_uppercase()) && models.contains('G'))
+        }
+    }
+}
+
+#[test]
+fn mooneye_acceptance() {
+    let dir = roms_dir().join("mooneye/acceptance");
+    let roms: Vec<PathBuf> = find_roms(&dir)
+        .into_iter()
+        .filter(|path| runs_on_dmg(path))
+        .collect();
+    if roms.is_empty() {
+        eprintln!("skipping: no ROMs under {}", dir.display());
+        return;
+    }
+    let results = roms
+        .into_iter()
+        .map(|path| {
+            let outcome = run_mooneye(&path, 600);
+            (path, outcome)
+        })
+        .collect();
+    assert_all_pass(results);
+}
+
+// ---------------------------------------------------------------------------
+// Screen hashes
+// ---------------------------------------------------------------------------
+
+/// ROMs under `screens/` with a sibling `.hash` file holding the expected
+/// frame hash in hex (as printed by the headless runner).
+#[test]
+fn screen_hashes() {
+    let dir = roms_dir().join("screens");
+    let mut results = Vec::new();
+    for path in find_roms(&dir) {
+        let Ok(hash) = std::fs::read_to_string(path.with_extension("hash")) else {
+            continue;
+        };
+        let expected = u64::from_str_radix(hash.trim().trim_start_matches("0x"), 16)
+            .unwrap_or_else(|_| panic!("{}: invalid hash file", path.display()));
+        let outcome = run_until_screen(&path, expected, 1200);
+        results.push((path, outcome));
+    }
+    if results.is_empty() {
+        eprintln!("skipping: no ROMs with .hash files under {}", dir.display());
+        return;
+    }
+    assert_all_pass(results);
+}
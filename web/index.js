@@ -1094,6 +1094,8 @@ document.querySelectorAll(".dbg-btn").forEach(btn => {
         const target = btn.dataset.target;
         visible[target] = !visible[target];
         document.getElementById(target).style.display = visible[target] ? "block" : "none";
+        if (target === "ilog-section" && emulator)
+            emulator.set_instruction_log_enabled(visible[target]);
         btn.classList.toggle("active", visible[target]);
     });
 });
@@ -1135,11 +1137,61 @@ function loop(now) {
     animFrame = requestAnimationFrame(loop);
 }
 
+// ── Battery saves ─────────────────────────────────────────────────────────────
+// Battery-backed cartridge RAM is kept in localStorage (base64 .sav bytes),
+// keyed by the ROM's header title and checksums.
+const SAVE_FLUSH_MS = 5000;
+let saveKey = null;
+let lastSave = null;
+
+function romSaveKey(romBytes) {
+    let title = "";
+    for (let i = 0x134; i < 0x144 && romBytes[i]; i++) title += String.fromCharCode(romBytes[i]);
+    const checksums = Array.from(romBytes.slice(0x14D, 0x150), b => b.toString(16).padStart(2, "0")).join("");
+    return `shrimp-save:${title}:${checksums}`;
+}
+
+function toBase64(bytes) {
+    let s = "";
+    for (const b of bytes) s += String.fromCharCode(b);
+    return btoa(s);
+}
+
+function fromBase64(str) {
+    return Uint8Array.from(atob(str), c => c.charCodeAt(0));
+}
+
+function flushSave() {
+    if (!emulator || !saveKey) return;
+    const data = emulator.export_save();
+    if (!data) return;
+    const encoded = toBase64(data);
+    if (encoded === lastSave) return;
+    try {
+        localStorage.setItem(saveKey, encoded);
+        lastSave = encoded;
+    } catch (err) {
+        console.error(err);
+    }
+}
+
+function restoreSave(romBytes) {
+    saveKey = romSaveKey(romBytes);
+    lastSave = localStorage.getItem(saveKey);
+    if (lastSave) emulator.import_save(fromBase64(lastSave));
+}
+
+setInterval(flushSave, SAVE_FLUSH_MS);
+window.addEventListener("pagehide", flushSave);
+
 async function startEmulator(romBytes) {
     if (animFrame !== null) { cancelAnimationFrame(animFrame); animFrame = null; }
+    flushSave();
     initAudio();
     if (audioCtx.state === "suspended") await audioCtx.resume();
     emulator = new Emulator(romBytes);
+    restoreSave(romBytes);
+    emulator.set_instruction_log_enabled(visible["ilog-section"]);
     // Allocate render buffers once per emulator session
     screenBuf = new Uint8ClampedArray(SCREEN_W * SCREEN_H * 4);
     screenImg = new ImageData(screenBuf, SCREEN_W, SCREEN_H);